@@ -1,3 +1,5 @@
+#![allow(clippy::let_and_return, clippy::clone_on_copy)]
+
 use criterion::{criterion_group, criterion_main, Criterion};
 use vtable::ched::{Token, CHED};
 use std::any::Any;
@@ -0,0 +1,119 @@
+//! `#[derive(Specialise)]` for user-defined vtable structs.
+//!
+//! Each field of the struct is populated with one of the standard thunks of the `vtable` crate,
+//! selected by the field's type (`CloneFn`, `DebugFn`, `DisplayFn`, `PartialEqFn`,
+//! `PartialOrdFn`, `OrdFn` or `HashFn`) or, failing that, by its name (`clone`, `debug`,
+//! `display`, `partial_eq`, `partial_cmp`, `cmp` or `hash`). The value type is bounded by the
+//! traits that the selected thunks require.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, Ident, Type, parse_macro_input};
+
+/// A standard thunk, along with the trait required of the value type.
+struct Thunk {
+    function: &'static str,
+    bound: proc_macro2::TokenStream,
+}
+
+impl Thunk {
+    fn by_type(ty: &Type) -> Option<Self> {
+        let Type::Path(path) = ty else {
+            return None;
+        };
+        match path.path.segments.last()?.ident.to_string().as_str() {
+            "CloneFn" => Some(Self::by_name("clone")?),
+            "DebugFn" => Some(Self::by_name("debug")?),
+            "DisplayFn" => Some(Self::by_name("display")?),
+            "PartialEqFn" => Some(Self::by_name("partial_eq")?),
+            "PartialOrdFn" => Some(Self::by_name("partial_cmp")?),
+            "OrdFn" => Some(Self::by_name("cmp")?),
+            "HashFn" => Some(Self::by_name("hash")?),
+            _ => None,
+        }
+    }
+
+    fn by_name(name: &str) -> Option<Self> {
+        let (function, bound) = match name {
+            "clone" => ("clone", quote!(::std::clone::Clone)),
+            "debug" => ("debug", quote!(::std::fmt::Debug)),
+            "display" => ("display", quote!(::std::fmt::Display)),
+            "partial_eq" => ("partial_eq", quote!(::std::cmp::PartialEq)),
+            "partial_cmp" => ("partial_cmp", quote!(::std::cmp::PartialOrd)),
+            "cmp" => ("cmp", quote!(::std::cmp::Ord)),
+            "hash" => ("hash", quote!(::std::hash::Hash)),
+            _ => return None,
+        };
+        Some(Self { function, bound })
+    }
+}
+
+#[proc_macro_derive(Specialise)]
+pub fn derive_specialise(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input,
+            "Specialise can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            &input,
+            "Specialise requires named fields",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "Specialise cannot be derived for generic structs",
+        ));
+    }
+
+    let value_type = Ident::new("__T", Span::call_site());
+    let mut initialisers = Vec::new();
+    let mut bounds = Vec::new();
+    let mut debug = false;
+    for field in &fields.named {
+        let ident = field.ident.as_ref().unwrap();
+        let thunk = Thunk::by_type(&field.ty)
+            .or_else(|| Thunk::by_name(&ident.to_string()))
+            .ok_or_else(|| {
+                Error::new_spanned(field, "no standard thunk matches this field's type or name")
+            })?;
+        let function = Ident::new(thunk.function, Span::call_site());
+        initialisers.push(quote!(#ident: ::vtable::#function::<#value_type>));
+        bounds.push(thunk.bound);
+        debug |= thunk.function == "debug";
+    }
+
+    let name = &input.ident;
+    let debug_fn = debug.then(|| {
+        quote! {
+            fn debug_fn() -> ::std::option::Option<::vtable::DebugFn> {
+                ::std::option::Option::Some(::vtable::debug::<#value_type>)
+            }
+        }
+    });
+    Ok(quote! {
+        impl<#value_type> ::vtable::vtable::Specialise<#value_type> for #name
+        where
+            #value_type: #(#bounds +)* 'static,
+        {
+            fn specialise() -> Self {
+                Self {
+                    #(#initialisers,)*
+                }
+            }
+
+            #debug_fn
+        }
+    })
+}
@@ -0,0 +1,134 @@
+//! A process-global table of vtables keyed by stable strings, shared by every version of the
+//! `vtable` crate linked into a binary.
+//!
+//! Distinct semver-incompatible versions of `vtable` have distinct registry types, statics and
+//! `Every` traits, and so cannot call one another's thunks. They may, however, depend on a
+//! common version of this crate, which is deliberately kept minimal so that it need never break
+//! compatibility. Vtables are published here in a version-independent form, [SharedVTable],
+//! whose thunks take the value by untyped pointer and are otherwise expressed in terms of the
+//! standard library alone. Entries are keyed by the names of the value and vtable types, which
+//! do not vary between versions, and guarded by the [TypeId] of the value type, as distinct
+//! types may share a name (e.g. those of two versions of a crate).
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt::Formatter;
+use std::hash::Hasher;
+use std::sync::{LazyLock, Mutex, PoisonError};
+
+static TABLE: LazyLock<Mutex<HashMap<(String, String), &'static SharedVTable>>> =
+    LazyLock::new(Default::default);
+
+/// The version-independent form of a vtable, published by one version of `vtable` and adopted
+/// by the others. Each thunk takes a pointer to a value of the type identified by `type_id`.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct SharedVTable {
+    pub type_id: TypeId,
+    pub debug: unsafe fn(*const (), &mut Formatter<'_>) -> std::fmt::Result,
+    pub display: unsafe fn(*const (), &mut Formatter<'_>) -> std::fmt::Result,
+
+    /// Compares two values of the same type.
+    pub eq: unsafe fn(*const (), *const ()) -> bool,
+    pub hash: unsafe fn(*const (), &mut dyn Hasher),
+
+    /// Whether the value's [TypeId] is to be hashed along with the value.
+    pub hash_type: bool,
+
+    /// Invoked with the value just before it is dropped.
+    pub drop_hook: unsafe fn(*mut ()),
+}
+
+/// Outcome of [get_or_insert].
+#[derive(Debug, Clone, Copy)]
+pub enum Lookup {
+    /// The vtable returned by `create` was published.
+    Published,
+
+    /// A vtable for the same type was published previously, e.g. by another version of `vtable`.
+    Found(&'static SharedVTable),
+
+    /// A vtable has been published under the same names, but for a different type.
+    Mismatch,
+}
+
+/// Looks up the vtable published under the given type names, publishing the one returned by
+/// `create` if there is none. Published vtables are leaked.
+pub fn get_or_insert(
+    value_type: &str,
+    vtable_type: &str,
+    type_id: TypeId,
+    create: impl FnOnce() -> SharedVTable,
+) -> Lookup {
+    let mut table = TABLE.lock().unwrap_or_else(PoisonError::into_inner);
+    let key = (value_type.to_owned(), vtable_type.to_owned());
+    if let Some(&published) = table.get(&key) {
+        return if published.type_id == type_id {
+            Lookup::Found(published)
+        } else {
+            Lookup::Mismatch
+        };
+    }
+    table.insert(key, Box::leak(Box::new(create())));
+    Lookup::Published
+}
+
+/// The vtable published under the given type names, if any.
+pub fn get(value_type: &str, vtable_type: &str) -> Option<&'static SharedVTable> {
+    let table = TABLE.lock().unwrap_or_else(PoisonError::into_inner);
+    table.get(&(value_type.to_owned(), vtable_type.to_owned())).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{get, get_or_insert, Lookup, SharedVTable};
+    use std::any::TypeId;
+    use std::fmt::Formatter;
+    use std::hash::Hasher;
+    use std::ptr;
+
+    unsafe fn debug(_: *const (), f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("shared")
+    }
+
+    unsafe fn eq(_: *const (), _: *const ()) -> bool {
+        true
+    }
+
+    unsafe fn hash(_: *const (), _: &mut dyn Hasher) {}
+
+    unsafe fn drop_hook(_: *mut ()) {}
+
+    fn vtable<T: 'static>() -> SharedVTable {
+        SharedVTable {
+            type_id: TypeId::of::<T>(),
+            debug,
+            display: debug,
+            eq,
+            hash,
+            hash_type: true,
+            drop_hook,
+        }
+    }
+
+    #[test]
+    fn publish_once() {
+        assert!(get("a", "b").is_none());
+        assert!(matches!(
+            get_or_insert("a", "b", TypeId::of::<u8>(), vtable::<u8>),
+            Lookup::Published
+        ));
+        let published = get("a", "b").unwrap();
+        assert_eq!(TypeId::of::<u8>(), published.type_id);
+
+        let Lookup::Found(found) = get_or_insert("a", "b", TypeId::of::<u8>(), || unreachable!())
+        else {
+            panic!("expected the published vtable");
+        };
+        assert!(ptr::eq(published, found));
+        assert!(matches!(
+            get_or_insert("a", "b", TypeId::of::<u16>(), || unreachable!()),
+            Lookup::Mismatch
+        ));
+    }
+}
@@ -0,0 +1,130 @@
+//! Cross-casting from `dyn Every` to other trait objects, such as `dyn Display` or `dyn Error`.
+//!
+//! A caster must first be registered for the concrete type and target trait object by calling
+//! [register]. Thereafter, `value.query::<dyn Trait>()` resolves the caster via a singleton
+//! registry keyed by the concrete [TypeId] and the target [TypeId].
+
+use crate::every::Every;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+
+type CastFn<U> = dyn for<'a> Fn(&'a dyn Every) -> &'a U + Sync + Send;
+
+/// Erased cast from `&dyn Every` to `&U`, where `U` is typically a trait object.
+struct Caster<U: ?Sized>(Box<CastFn<U>>);
+
+#[derive(Default)]
+struct Registry {
+    casters: RwLock<HashMap<(TypeId, TypeId), Arc<dyn Any + Sync + Send>>>,
+}
+
+impl Registry {
+    fn singleton() -> &'static Registry {
+        static LAZY: LazyLock<Registry> = LazyLock::new(Default::default);
+        &LAZY
+    }
+
+    fn insert<T: 'static, U: ?Sized + 'static>(&self, cast: fn(&T) -> &U) {
+        let caster = Caster::<U>(Box::new(move |value| {
+            // SAFETY: the caster is only looked up for values whose type ID matches T
+            cast(unsafe { value.downcast_ref_unchecked::<T>() })
+        }));
+        let key = (TypeId::of::<T>(), TypeId::of::<U>());
+        self.casters.write().unwrap().insert(key, Arc::new(caster));
+    }
+
+    fn get(&self, type_id: TypeId, target: TypeId) -> Option<Arc<dyn Any + Sync + Send>> {
+        self.casters.read().unwrap().get(&(type_id, target)).cloned()
+    }
+}
+
+/// Registers a caster from [T] to [U], replacing any previously registered caster for the pair.
+/// The cast is typically an unsizing coercion, for example:
+///
+/// ```
+/// use std::fmt::Display;
+/// use vtable::cast;
+/// use vtable::every::Every;
+///
+/// cast::register::<i32, dyn Display>(|value| value);
+/// let value = &42 as &dyn Every;
+/// assert_eq!("42", value.query::<dyn Display>().unwrap().to_string());
+/// ```
+pub fn register<T: 'static, U: ?Sized + 'static>(cast: fn(&T) -> &U) {
+    Registry::singleton().insert(cast);
+}
+
+impl dyn Every {
+    /// Cross-casts the value to `&U` using a caster registered with [register], returning `None`
+    /// if no caster has been registered for the value's concrete type.
+    #[inline]
+    pub fn query<U: ?Sized + 'static>(&self) -> Option<&U> {
+        let caster = Registry::singleton().get(self.type_id(), TypeId::of::<U>())?;
+        let caster = caster.downcast_ref::<Caster<U>>().unwrap();
+        Some((caster.0)(self))
+    }
+}
+
+impl dyn Every + Send {
+    #[inline]
+    pub fn query<U: ?Sized + 'static>(&self) -> Option<&U> {
+        <dyn Every>::query::<U>(self)
+    }
+}
+
+impl dyn Every + Send + Sync {
+    #[inline]
+    pub fn query<U: ?Sized + 'static>(&self) -> Option<&U> {
+        <dyn Every>::query::<U>(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cast::register;
+    use crate::every::Every;
+    use std::error::Error;
+    use std::fmt::{Debug, Display, Formatter};
+
+    #[derive(Debug)]
+    struct Failure;
+
+    impl Display for Failure {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "failure")
+        }
+    }
+
+    impl Error for Failure {}
+
+    #[test]
+    fn query_registered() {
+        register::<Failure, dyn Display>(|value| value);
+        register::<Failure, dyn Error>(|value| value);
+        let val = Box::new(Failure) as Box<dyn Every>;
+        assert_eq!("failure", val.query::<dyn Display>().unwrap().to_string());
+        assert_eq!("failure", val.query::<dyn Error>().unwrap().to_string());
+    }
+
+    #[test]
+    fn query_unregistered() {
+        struct Unregistered;
+        let val = Box::new(Unregistered) as Box<dyn Every>;
+        assert!(val.query::<dyn Display>().is_none());
+    }
+
+    #[test]
+    fn query_unregistered_trait() {
+        register::<Failure, dyn Display>(|value| value);
+        let val = Box::new(Failure) as Box<dyn Every>;
+        assert!(val.query::<dyn Debug>().is_none());
+    }
+
+    #[test]
+    fn query_send_sync() {
+        register::<u64, dyn Display>(|value| value);
+        let val = Box::new(42u64) as Box<dyn Every + Send + Sync>;
+        assert_eq!("42", val.query::<dyn Display>().unwrap().to_string());
+    }
+}
@@ -1,205 +1,3560 @@
 //! A dynamic object supporting [Clone], [Hash], [Eq], and [Debug] traits.
+//!
+//! [Display] is also supported: it falls back to the [Debug] rendering unless a [Display] thunk
+//! is substituted with [`VTable::with_display()`].
 
-use std::fmt::{Debug, Formatter};
-use std::hash::{Hash, Hasher};
-use crate::every::Every;
-use crate::{clone, debug, hash, partial_eq, vtable, CloneFn, DebugFn, HashFn, PartialEqFn};
+use std::any::{self, Any, TypeId};
+use std::borrow::Borrow;
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{BuildHasher, Hash, Hasher, RandomState};
+use std::mem::{self, ManuallyDrop, MaybeUninit};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::alloc::{self, Layout};
+use std::ptr::{self, NonNull};
+use std::sync::{Arc, LazyLock};
+use crate::diff::DiffReport;
+use crate::every::{panic, BoxDowncast, DowncastError, DowncastItem, Every};
+use crate::{
+    clone, debug, hash, partial_eq, vtable, CloneFn, DebugFn, DeepSizeFn, DiffFn, DisplayFn, HashFn,
+    PartialEqFn, TryCloneFn,
+};
+use crate::pool::Pool;
 use crate::vtable::Specialise;
 
 pub type Token<T> = vtable::Token<T, VTable>;
 
+/// The number of bytes of a value that may be stored inline, sparing a heap allocation: 16 by
+/// default, or 24 with the `inline-24` feature.
+pub const INLINE_CAPACITY: usize = mem::size_of::<InlineBuf>();
+
+const INLINE_WORDS: usize = if cfg!(feature = "inline-24") { 3 } else { 2 };
+
+type InlineBuf = MaybeUninit<[usize; INLINE_WORDS]>;
+
+/// Whether values of [T] fit within the inline buffer of a [CHED].
+const fn fits_inline<T>() -> bool {
+    mem::size_of::<T>() <= mem::size_of::<InlineBuf>()
+        && mem::align_of::<T>() <= mem::align_of::<InlineBuf>()
+}
+
+enum Storage {
+    Boxed(Box<dyn Every>),
+
+    /// A value laid out at the start of the buffer, dropped by the owning [CHED] through its
+    /// vtable.
+    Inline(InlineBuf),
+
+    /// A value in a slot allocated by a [Pool], to which it is released by the owning [CHED].
+    Pooled(NonNull<dyn Every>),
+}
+
 pub struct CHED {
-    inner: Box<dyn Every>,
+    storage: Storage,
     vtable: &'static VTable,
 }
 
 impl CHED {
+    /// Constructs a [CHED], storing the value inline if its size and alignment (as recorded in
+    /// the vtable) permit, or boxing it otherwise.
     #[inline]
     pub fn new<T: 'static>(value: T, tok: &Token<T>) -> Self {
+        let vtable = tok.vtable_ref();
+        let storage = if vtable.inline && (vtable.type_id)() == TypeId::of::<T>() {
+            let mut buf = InlineBuf::uninit();
+            // SAFETY: the vtable was specialised for T, which thus fits within the buffer
+            unsafe { buf.as_mut_ptr().cast::<T>().write(value) };
+            Storage::Inline(buf)
+        } else {
+            Storage::Boxed(Box::new(value))
+        };
+        Self { storage, vtable }
+    }
+
+    /// Constructs a [CHED] from an already boxed value, adopting the box without moving or
+    /// reallocating the value, which is thus never stored inline.
+    #[inline]
+    pub fn new_boxed<T: 'static>(value: Box<T>, tok: &Token<T>) -> Self {
+        Self::from_parts(value, tok.vtable_ref())
+    }
+
+    /// Constructs a [CHED] whose value, unless stored inline, occupies a block recycled from (and
+    /// eventually returned to) the given pool, sparing the allocator.
+    #[inline]
+    pub fn new_pooled<T: 'static>(value: T, tok: &Token<T>, pool: &Pool) -> Self {
+        let vtable = tok.vtable_ref();
+        if vtable.inline || (vtable.type_id)() != TypeId::of::<T>() {
+            return Self::new(value, tok);
+        }
+        let slot = pool.allocate(Layout::new::<T>()).cast::<T>();
+        // SAFETY: the slot was allocated for T
+        unsafe { slot.write(value) };
         Self {
-            inner: Box::new(value),
-            vtable: tok.vtable_ref(),
+            storage: Storage::Pooled(slot),
+            vtable,
         }
     }
 
+    /// Constructs a [CHED] whose value, unless stored inline, occupies a block obtained from the
+    /// given allocator, e.g. an arena or slab, to keep it off the global allocator. The block is
+    /// returned to the allocator when the value is dropped; clones draw from the same allocator.
+    #[cfg(feature = "allocator_api")]
     #[inline]
-    #[allow(clippy::borrowed_box)]
-    pub fn inner(&self) -> &Box<dyn Every> {
-        &self.inner
+    pub fn new_in<T: 'static, A: crate::pool::Allocator + 'static>(
+        value: T,
+        tok: &Token<T>,
+        allocator: &Arc<A>,
+    ) -> Self {
+        let vtable = tok.vtable_ref();
+        if vtable.inline || (vtable.type_id)() != TypeId::of::<T>() {
+            return Self::new(value, tok);
+        }
+        let allocator = Arc::clone(allocator) as Arc<dyn crate::pool::Allocator>;
+        let slot = crate::pool::allocate_in(allocator, Layout::new::<T>()).cast::<T>();
+        // SAFETY: the slot was allocated for T
+        unsafe { slot.write(value) };
+        Self {
+            storage: Storage::Pooled(slot),
+            vtable,
+        }
     }
 
+    /// Constructs a [CHED] only if a vtable for [T] was already specialised in the singleton
+    /// [Registry](vtable::Registry), e.g. during startup, without ever writing to the registry.
+    /// Otherwise, the value is handed back.
     #[inline]
-    pub fn inner_mut(&mut self) -> &mut Box<dyn Every> {
-        &mut self.inner
+    pub fn try_new<T: 'static>(value: T) -> Result<Self, Unregistered<T>> {
+        match Token::try_existing() {
+            Some(tok) => Ok(Self::new(value, &tok)),
+            None => Err(Unregistered(value)),
+        }
     }
 
+    /// Pairs an erased value with the vtable of the given token, provided the value is of type [T].
     #[inline]
-    pub fn into_inner(self) -> Box<dyn Every> {
-        self.inner
+    pub fn from_every<T: 'static>(
+        value: Box<dyn Every>,
+        tok: &Token<T>,
+    ) -> Result<Self, DowncastError> {
+        value.downcast_ref::<T>()?;
+        Ok(Self::from_parts(value, tok.vtable_ref()))
     }
-}
 
-pub struct VTable {
-    clone: CloneFn,
-    debug: DebugFn,
-    partial_eq: PartialEqFn,
-    hash: HashFn,
-}
+    /// Constructs a [CHED] without a caller-supplied token, acquiring one for [T] from the
+    /// global registry (cached per thread by [global_token](vtable::global_token)).
+    ///
+    /// A blanket `From<T>` is not offered as it would overlap with the reflexive `From<CHED>`.
+    #[inline]
+    pub fn new_auto<T: Clone + Debug + Eq + Hash + 'static>(value: T) -> Self {
+        Self::new(value, vtable::global_token())
+    }
 
-impl<T: Clone + Debug + Eq + Hash + 'static> Specialise<T> for VTable {
-    fn specialise() -> Self {
+    /// Pairs an erased value with a vtable resolved for its type at runtime. The caller must
+    /// ensure that the vtable was specialised for the value's type.
+    #[inline]
+    pub(crate) fn from_parts(inner: Box<dyn Every>, vtable: &'static VTable) -> Self {
         Self {
-            clone: clone::<T>,
-            debug: debug::<T>,
-            partial_eq: partial_eq::<T>,
-            hash: hash::<T>,
+            storage: Storage::Boxed(inner),
+            vtable,
         }
     }
-}
 
-impl Debug for CHED {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        (self.vtable.debug)(&*self.inner, f)
+    /// Disassembles the [CHED] without dropping the value. An inline value must subsequently be
+    /// moved out of the buffer or dropped in place.
+    #[inline]
+    fn into_parts(self) -> (Storage, &'static VTable) {
+        let this = ManuallyDrop::new(self);
+        // SAFETY: the storage is read exactly once and the original is not dropped
+        (unsafe { ptr::read(&this.storage) }, this.vtable)
+    }
+
+    /// The memory attributable to the [CHED]: its own size, that of the value if stored out of
+    /// line, and the heap memory owned by the value. The last is only counted if the vtable
+    /// carries a thunk attached with [`VTable::with_deep_size()`].
+    pub fn deep_size_of(&self) -> usize {
+        let out_of_line = match self.storage {
+            Storage::Inline(_) => 0,
+            Storage::Boxed(_) | Storage::Pooled(_) => mem::size_of_val(self.inner()),
+        };
+        let children = self.vtable.deep_size.map_or(0, |deep_size| deep_size(self.inner()));
+        mem::size_of::<Self>() + out_of_line + children
+    }
+
+    /// A hash that is stable across processes, platforms and program runs, computed with a fixed
+    /// seed over the [StableTypeId](crate::stable::StableTypeId) of the value and a canonical
+    /// encoding of its serialized form. Requires a vtable carrying a serialization thunk.
+    #[cfg(feature = "serde")]
+    pub fn stable_hash(&self) -> Result<u64, crate::content::ContentError> {
+        use crate::content::ContentError;
+        use crate::stable::{fnv1a, FNV_OFFSET};
+
+        let serialize = self.vtable.serialize.ok_or_else(|| {
+            ContentError(format!("no serialization thunk for {}", self.type_name()))
+        })?;
+        let content = serialize(self.inner())?;
+        let hash = fnv1a(FNV_OFFSET, &self.inner().stable_type_id().0.to_le_bytes());
+        Ok(fnv1a(hash, &content.canonical_bytes()))
+    }
+
+    /// The vtable with which the value is paired.
+    #[inline]
+    pub fn vtable(&self) -> &'static VTable {
+        self.vtable
+    }
+
+    /// Recovers the token for the value's type, e.g. to construct further [CHED]s of [T] without
+    /// consulting the registry.
+    #[inline]
+    pub fn token<T: Clone + Debug + Eq + Hash + 'static>(&self) -> Result<Token<T>, DowncastError> {
+        self.downcast_ref::<T>()?;
+        Ok(Token::from_static(self.vtable))
+    }
+
+    /// Whether the value is stored inline rather than on the heap.
+    #[inline]
+    pub fn is_inline(&self) -> bool {
+        matches!(self.storage, Storage::Inline(_))
+    }
+
+    /// Whether the value occupies a block allocated by a [Pool] or, with the `allocator_api`
+    /// feature, a caller-provided allocator.
+    #[inline]
+    pub fn is_pooled(&self) -> bool {
+        matches!(self.storage, Storage::Pooled(_))
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &dyn Every {
+        match &self.storage {
+            Storage::Boxed(inner) => &**inner,
+            // SAFETY: an inline value is only stored with a vtable specialised for its type
+            Storage::Inline(buf) => unsafe {
+                &*(self.vtable.erase)(buf.as_ptr().cast_mut().cast())
+            },
+            // SAFETY: a pooled value is initialised until it is released
+            Storage::Pooled(value) => unsafe { value.as_ref() },
+        }
+    }
+
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut dyn Every {
+        match &mut self.storage {
+            Storage::Boxed(inner) => &mut **inner,
+            // SAFETY: an inline value is only stored with a vtable specialised for its type
+            Storage::Inline(buf) => unsafe { &mut *(self.vtable.erase)(buf.as_mut_ptr().cast()) },
+            // SAFETY: a pooled value is initialised until it is released
+            Storage::Pooled(value) => unsafe { value.as_mut() },
+        }
+    }
+
+    /// Unwraps the erased value, boxing it if it was stored inline.
+    #[inline]
+    pub fn into_inner(self) -> Box<dyn Every> {
+        match self.into_parts() {
+            (Storage::Boxed(inner), _) => inner,
+            // SAFETY: an inline value is only stored with a vtable specialised for its type
+            (Storage::Inline(mut buf), vtable) => unsafe { (vtable.take)(buf.as_mut_ptr().cast()) },
+            // SAFETY: a pooled value is only stored with a vtable specialised for its type
+            (Storage::Pooled(value), vtable) => unsafe {
+                let inner = (vtable.take)(value.as_ptr().cast());
+                Pool::release(value.cast());
+                inner
+            },
+        }
+    }
+
+    /// Unwraps the erased value as a [`Box<dyn Any>`](Any), as per
+    /// [`into_inner()`](Self::into_inner).
+    #[inline]
+    pub fn into_any(self) -> Box<dyn Any> {
+        self.into_inner()
+    }
+
+    /// The name of the contained value's type.
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.inner().type_name()
+    }
+
+    /// The [TypeId] of the contained value, as opposed to that of [CHED] itself.
+    #[inline]
+    pub fn type_id(&self) -> TypeId {
+        self.inner().type_id()
+    }
+
+    #[inline]
+    pub fn is<T: 'static>(&self) -> bool {
+        self.inner().is::<T>()
+    }
+
+    #[inline]
+    pub fn downcast_ref<T: 'static>(&self) -> Result<&T, DowncastError> {
+        self.inner().downcast_ref()
+    }
+
+    #[inline]
+    pub fn downcast_mut<T: 'static>(&mut self) -> Result<&mut T, DowncastError> {
+        self.inner_mut().downcast_mut()
+    }
+
+    #[inline]
+    pub fn downcast<T: 'static>(self) -> Result<T, DowncastError> {
+        self.downcast_ref::<T>()?;
+        match self.into_parts() {
+            (Storage::Boxed(inner), _) => inner.downcast(),
+            // SAFETY: just checked the type
+            (Storage::Inline(buf), _) => Ok(unsafe { buf.as_ptr().cast::<T>().read() }),
+            // SAFETY: just checked the type
+            (Storage::Pooled(value), _) => unsafe {
+                let inner = value.cast::<T>().read();
+                Pool::release(value.cast());
+                Ok(inner)
+            },
+        }
+    }
+
+    /// Applies [f] to the value, if it is of type [T].
+    #[inline]
+    pub fn map_ref<T: 'static, R>(&self, f: impl FnOnce(&T) -> R) -> Result<R, DowncastError> {
+        self.downcast_ref().map(f)
+    }
+
+    /// Applies [f] to the mutably borrowed value, if it is of type [T].
+    #[inline]
+    pub fn map_mut<T: 'static, R>(
+        &mut self,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Result<R, DowncastError> {
+        self.downcast_mut().map(f)
+    }
+
+    /// Compares the value with a plain one using the vtable's equality thunk, sparing the
+    /// construction of a second [CHED]. Values of differing types are unequal.
+    #[inline]
+    pub fn eq_value<T: 'static>(&self, value: &T) -> bool {
+        (self.vtable.partial_eq)(self.inner(), value)
+    }
+
+    /// Explains how the value differs from another, e.g. to elaborate on a failed assertion. Uses
+    /// the vtable's diff thunk if one is attached with [`VTable::with_diff()`], and otherwise
+    /// compares the lines of the pretty-printed [Debug] renderings. The report is empty if the
+    /// values are equal.
+    pub fn diff(&self, other: &CHED) -> DiffReport {
+        if self.type_id() != other.type_id() {
+            let mut report = DiffReport::new();
+            let (lhs, rhs) = (self.type_name(), other.type_name());
+            report.push("type", &format_args!("{lhs}"), &format_args!("{rhs}"));
+            return report;
+        }
+        if self == other {
+            return DiffReport::new();
+        }
+        if let Some(diff) = self.vtable.diff {
+            return diff(self.inner(), other.inner());
+        }
+        let (lhs, rhs) = (format!("{self:#?}"), format!("{other:#?}"));
+        let mut report = DiffReport::from_debug(&lhs, &rhs);
+        if report.is_empty() {
+            // unequal values that render alike
+            report.push("", &format_args!("{lhs}"), &format_args!("{rhs}"));
+        }
+        report
+    }
+
+    /// Whether both refer to the same value in memory, i.e., are one and the same [CHED]; a
+    /// cheap identity check that never consults the equality thunk. Zero-sized values stored
+    /// out of line may be indistinguishable.
+    #[inline]
+    pub fn ptr_eq(&self, other: &CHED) -> bool {
+        ptr::addr_eq(self.inner(), other.inner())
+    }
+
+    /// Duplicates the value, surfacing a failure of a vtable built with
+    /// [`VTable::of_try_clone()`] rather than panicking as [`Clone::clone()`] would. Values whose
+    /// vtable clones infallibly are cloned as usual, whereas fallible duplicates are
+    /// always boxed.
+    pub fn try_clone(&self) -> Result<Self, CloneError> {
+        match self.vtable.try_clone {
+            Some(try_clone) => Ok(Self::from_parts(try_clone(self.inner())?, self.vtable)),
+            None => Ok(self.clone()),
+        }
+    }
+
+    /// Hashes the value with a hasher built by the given [BuildHasher], e.g. for sharding or a
+    /// bloom filter.
+    #[inline]
+    pub fn hash_one(&self, build_hasher: &impl BuildHasher) -> u64 {
+        build_hasher.hash_one(self)
+    }
+
+    /// Hashes the value with a [RandomState] seeded once per process, such that equal values
+    /// hash alike for the lifetime of the process.
+    #[inline]
+    pub fn hash_code(&self) -> u64 {
+        static SEED: LazyLock<RandomState> = LazyLock::new(RandomState::new);
+        self.hash_one(&*SEED)
+    }
+
+    /// Swaps in a new value of the same type, returning the old one and retaining the vtable.
+    #[inline]
+    pub fn replace<T: 'static>(&mut self, value: T) -> Result<T, DowncastError> {
+        Ok(mem::replace(self.downcast_mut()?, value))
+    }
+
+    /// Consumes the [CHED], extracting a value of type [T], provided it is held under the
+    /// token's vtable. Unlike [`downcast()`](Self::downcast), the [CHED] is handed back intact
+    /// if the value is of a different type, or carries a different vtable, e.g. one acquired
+    /// before the registry entry was overridden.
+    #[inline]
+    pub fn take_with_token<T: 'static>(self, tok: &Token<T>) -> Result<T, Self> {
+        if ptr::eq(self.vtable, tok.vtable_ref()) && self.is::<T>() {
+            Ok(self.downcast().unwrap_or_else(panic))
+        } else {
+            Err(self)
+        }
     }
 }
 
-impl Clone for CHED {
+impl Drop for CHED {
     #[inline]
-    fn clone(&self) -> Self {
-        Self {
-            inner: (self.vtable.clone)(&*self.inner),
-            vtable: self.vtable,
+    fn drop(&mut self) {
+        if let Some(on_drop) = self.vtable.on_drop {
+            on_drop(self.inner_mut());
+        }
+        match &mut self.storage {
+            Storage::Boxed(_) => {}
+            // SAFETY: an inline value is only stored with a vtable specialised for its type
+            Storage::Inline(buf) => unsafe {
+                ptr::drop_in_place((self.vtable.erase)(buf.as_mut_ptr().cast()))
+            },
+            // SAFETY: a pooled value is initialised until it is released
+            Storage::Pooled(value) => unsafe {
+                ptr::drop_in_place(value.as_ptr());
+                Pool::release(value.cast());
+            },
         }
     }
 }
 
-impl PartialEq for CHED {
+impl From<CHED> for Box<dyn Every> {
     #[inline]
-    fn eq(&self, other: &Self) -> bool {
-        (self.vtable.partial_eq)(&*self.inner, &*other.inner)
+    fn from(value: CHED) -> Self {
+        value.into_inner()
     }
 }
 
-impl Eq for CHED {}
+/// Exposes the methods of the value's `dyn Every`. Note that, being [Every] itself, a `&CHED`
+/// passed where a `&dyn Every` is expected erases the [CHED] rather than the value; dereference
+/// it with `&*` (or use [`as_ref()`](AsRef::as_ref)) to pass the value.
+impl Deref for CHED {
+    type Target = dyn Every;
 
-impl Hash for CHED {
     #[inline]
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        (self.vtable.hash)(&*self.inner, state);
+    fn deref(&self) -> &Self::Target {
+        self.inner()
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::every::{panic, BoxDowncast};
-    use crate::vtable::Token;
-    use crate::ched::CHED;
-    use std::collections::HashMap;
-
-    #[test]
-    fn self_is_equal() {
-        let obj = CHED::new(42, &Token::default());
-        assert_eq!(obj, obj);
+impl AsRef<dyn Every> for CHED {
+    #[inline]
+    fn as_ref(&self) -> &(dyn Every + 'static) {
+        self.inner()
     }
+}
 
-    #[test]
-    fn same_value_equal() {
-        let vtable_tok_i32 = Token::default();
-        let obj_1 = CHED::new(42, &vtable_tok_i32);
-        let obj_2 = CHED::new(42, &vtable_tok_i32);
-        assert_eq!(obj_1, obj_2);
+impl AsMut<dyn Every> for CHED {
+    #[inline]
+    fn as_mut(&mut self) -> &mut (dyn Every + 'static) {
+        self.inner_mut()
     }
+}
 
-    #[test]
-    fn cloned_value_equal() {
-        let obj_1 = CHED::new(42, &Token::default());
-        let obj_2 = obj_1.clone();
-        assert_eq!(obj_1, obj_2);
+impl DowncastItem for CHED {
+    type Output<T: 'static> = T;
+
+    #[inline]
+    fn downcast_item<T: 'static>(self) -> Option<T> {
+        self.downcast().ok()
     }
+}
 
-    #[test]
-    fn different_values_not_equal() {
-        let vtable_tok_i32 = Token::default();
+impl<'a> DowncastItem for &'a CHED {
+    type Output<T: 'static> = &'a T;
 
-        let obj_1 = CHED::new(42, &vtable_tok_i32);
-        let obj_2 = CHED::new(43, &vtable_tok_i32);
-        assert_ne!(obj_1, obj_2);
+    #[inline]
+    fn downcast_item<T: 'static>(self) -> Option<&'a T> {
+        self.downcast_ref().ok()
     }
+}
 
-    #[test]
-    fn different_types_not_equal() {
-        let obj_1 = CHED::new(42, &Token::default());
-        let obj_2 = CHED::new("foo", &Token::default());
-        assert_ne!(obj_1, obj_2);
+/// The error returned by [`CHED::try_new()`] when no vtable was specialised for [T], carrying the
+/// rejected value.
+pub struct Unregistered<T>(pub T);
+
+impl<T> Debug for Unregistered<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unregistered<{}>", any::type_name::<T>())
     }
+}
 
-    #[test]
-    fn test_debug() {
-        let obj = CHED::new(42, &Token::default());
-        let debug = format!("{obj:?}");
-        assert_eq!("42", debug);
+impl<T> Display for Unregistered<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no vtable registered for {}", any::type_name::<T>())
     }
+}
 
-    #[test]
-    fn test_hash() {
-        let vtable_tok_i32 = Token::default();
-        let vtable_tok_str_slice = Token::default();
+impl<T> Error for Unregistered<T> {}
 
-        let mut map = HashMap::new();
-        assert!(map.insert(CHED::new(42, &vtable_tok_i32), ()).is_none());
-        assert!(map.insert(CHED::new(43, &vtable_tok_i32), ()).is_none());
-        assert!(map
-            .insert(CHED::new("foo", &vtable_tok_str_slice), ())
-            .is_none());
+/// Duplication that may fail, e.g. for wrappers around file handles or connections. Such types
+/// are given a vtable with [`VTable::of_try_clone()`] and duplicated with [`CHED::try_clone()`].
+pub trait TryClone: Sized {
+    fn try_clone(&self) -> Result<Self, CloneError>;
+}
 
-        assert!(map.insert(CHED::new(42, &vtable_tok_i32), ()).is_some());
-        assert!(map.insert(CHED::new(43, &vtable_tok_i32), ()).is_some());
-        assert!(map
-            .insert(CHED::new("foo", &vtable_tok_str_slice), ())
-            .is_some());
-    }
+/// The reason a value could not be duplicated.
+#[derive(Debug)]
+pub struct CloneError(pub Box<dyn Error + Send + Sync>);
 
-    #[test]
-    fn downcast_ref() {
-        let obj = CHED::new(42i32, &Token::default());
-        assert_eq!(
-            &42i32,
-            obj.inner().downcast_ref::<i32>().unwrap_or_else(panic)
-        );
+impl CloneError {
+    #[inline]
+    pub fn new(source: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        Self(source.into())
     }
+}
 
-    #[test]
-    fn downcast_mut() {
-        let mut obj_1 = CHED::new(42i32, &Token::default());
-        let obj_2 = obj_1.clone();
-        *obj_1.inner_mut().downcast_mut().unwrap() = 13;
-        assert_eq!(
-            &13i32,
-            obj_1.inner().downcast_ref::<i32>().unwrap_or_else(panic)
-        );
-        assert_eq!(
-            &42i32,
-            obj_2.inner().downcast_ref::<i32>().unwrap_or_else(panic)
-        );
+impl Display for CloneError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "clone failed: {}", self.0)
     }
+}
 
-    #[test]
-    fn downcast() {
-        let obj = CHED::new(42i32, &Token::default());
-        assert_eq!(42i32, obj.into_inner().downcast::<i32>().unwrap());
+impl Error for CloneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.0)
     }
+}
 
-    #[test]
-    #[should_panic(expected = "cannot downcast i32 into u32")]
-    fn downcast_ref_with_wrong_type() {
-        let obj = CHED::new(42i32, &Token::default());
-        let _: &u32 = obj.inner().downcast_ref().unwrap_or_else(panic);
-    }
+/// Invoked with a value that is about to be dropped; see [`VTable::with_drop_hook()`].
+pub type DropHookFn = fn(&mut dyn Every);
 
-    #[test]
-    #[should_panic(expected = "cannot downcast i32 into u32")]
-    fn downcast_with_wrong_type() {
-        let obj = CHED::new(42i32, &Token::default());
-        let _: u32 = obj.into_inner().downcast().unwrap_or_else(panic);
+pub struct VTable {
+    clone: CloneFn,
+    debug: DebugFn,
+    display: DisplayFn,
+    partial_eq: PartialEqFn,
+    hash: HashFn,
+    hash_type: bool,
+    #[cfg(feature = "serde")]
+    serialize: Option<crate::SerializeFn>,
+    deep_size: Option<DeepSizeFn>,
+    diff: Option<DiffFn>,
+    on_drop: Option<DropHookFn>,
+    try_clone: Option<TryCloneFn>,
+
+    // inline storage, consulted only once the type has been verified
+    type_id: fn() -> TypeId,
+    inline: bool,
+    erase: fn(*mut ()) -> *mut dyn Every,
+    clone_into: unsafe fn(&dyn Every, *mut ()),
+    take: unsafe fn(*mut ()) -> Box<dyn Every>,
+
+    // thin storage, likewise
+    thin_layout: Layout,
+    thin_offset: usize,
+}
+
+fn erase<T: 'static>(ptr: *mut ()) -> *mut dyn Every {
+    ptr.cast::<T>()
+}
+
+/// # Safety
+/// [dst] must be valid for writes of [T].
+unsafe fn clone_into<T: Clone + 'static>(this: &dyn Every, dst: *mut ()) {
+    let value = this.downcast_ref::<T>().unwrap_or_else(panic);
+    unsafe { dst.cast::<T>().write(value.clone()) }
+}
+
+fn clone_or_panic<T: TryClone + 'static>(this: &dyn Every) -> Box<dyn Every> {
+    crate::try_clone::<T>(this).unwrap_or_else(|err| panic!("{}: {err}", any::type_name::<T>()))
+}
+
+/// # Safety
+/// [dst] must be valid for writes of [T].
+unsafe fn try_clone_into<T: TryClone + 'static>(this: &dyn Every, dst: *mut ()) {
+    let value = this.downcast_ref::<T>().unwrap_or_else(panic);
+    let cloned = value
+        .try_clone()
+        .unwrap_or_else(|err| panic!("{}: {err}", any::type_name::<T>()));
+    unsafe { dst.cast::<T>().write(cloned) }
+}
+
+/// # Safety
+/// [src] must point to an initialised [T], which is moved out.
+unsafe fn take<T: 'static>(src: *mut ()) -> Box<dyn Every> {
+    Box::new(unsafe { src.cast::<T>().read() })
+}
+
+impl VTable {
+    /// Constructs a [T]-specialised vtable in a `const` context, allowing it to be placed in a
+    /// `static` item and paired with [`Token::from_static`](vtable::Token::from_static).
+    pub const fn of<T: Clone + Debug + Eq + Hash + 'static>() -> Self {
+        Self::with_cloning::<T>(clone::<T>, clone_into::<T>, None)
+    }
+
+    /// Constructs a [T]-specialised vtable for a type whose duplication may fail, in a `const`
+    /// context. [`CHED::try_clone()`] surfaces a failure, whereas [`Clone::clone()`] panics on
+    /// one. As [VTable] cannot be [specialised](Specialise) for a type lacking [Clone], tokens are
+    /// created with [`Token::from_static_checked()`].
+    pub const fn of_try_clone<T: TryClone + Debug + Eq + Hash + 'static>() -> Self {
+        Self::with_cloning::<T>(
+            clone_or_panic::<T>,
+            try_clone_into::<T>,
+            Some(crate::try_clone::<T>),
+        )
+    }
+
+    /// Constructs a [T]-specialised vtable, in a `const` context, that wipes values implementing
+    /// [Zeroize](::zeroize::Zeroize) as they are dropped. Wiping is best-effort: copies made by
+    /// moving a value, or left behind when a collection reallocates, are not reached.
+    #[cfg(feature = "zeroize")]
+    pub const fn of_zeroizing<T: Clone + Debug + Eq + Hash + ::zeroize::Zeroize + 'static>(
+    ) -> Self {
+        Self::of::<T>().with_drop_hook(crate::zeroize::<T>)
+    }
+
+    const fn with_cloning<T: Debug + Eq + Hash + 'static>(
+        clone: CloneFn,
+        clone_into: unsafe fn(&dyn Every, *mut ()),
+        try_clone: Option<TryCloneFn>,
+    ) -> Self {
+        Self {
+            clone,
+            debug: debug::<T>,
+            display: debug::<T>,
+            partial_eq: partial_eq::<T>,
+            hash: hash::<T>,
+            hash_type: true,
+            #[cfg(feature = "serde")]
+            serialize: None,
+            deep_size: None,
+            diff: None,
+            on_drop: None,
+            try_clone,
+            type_id: TypeId::of::<T>,
+            inline: fits_inline::<T>(),
+            erase: erase::<T>,
+            clone_into,
+            take: take::<T>,
+            thin_layout: Layout::new::<ThinBlock<T>>(),
+            thin_offset: mem::offset_of!(ThinBlock<T>, value),
+        }
+    }
+
+    /// Substitutes the [Debug] thunk, e.g. to format a type differently when registering the
+    /// vtable with [`Registry::register()`](vtable::Registry::register).
+    pub const fn with_debug(self, debug: DebugFn) -> Self {
+        Self { debug, ..self }
+    }
+
+    /// Substitutes the [Display] thunk, which otherwise renders the value with [Debug]. Types
+    /// implementing [Display] may pass [`display::<T>`](crate::display).
+    pub const fn with_display(self, display: DisplayFn) -> Self {
+        Self { display, ..self }
+    }
+
+    /// Substitutes both the [Debug] and [Display] thunks with [redacted](crate::redacted()),
+    /// rendering values as `<redacted {type}>` whilst comparison, hashing and cloning continue
+    /// to work. See [`register_redacted()`](Self::register_redacted) to also redact the
+    /// diagnostics of the registry.
+    pub const fn redacted(self) -> Self {
+        Self {
+            debug: crate::redacted,
+            display: crate::redacted,
+            ..self
+        }
+    }
+
+    /// Registers a [redacted](Self::redacted) vtable for [T] and [redacts](Registry::redact) the
+    /// type in the registry's diagnostics, e.g. for credentials carried in dynamic property bags.
+    ///
+    /// [Registry]: vtable::Registry
+    pub fn register_redacted<T: Clone + Debug + Eq + Hash + 'static>(
+        registry: &vtable::Registry,
+    ) -> Token<T> {
+        registry.redact::<T>();
+        registry.register(Self::of::<T>().redacted())
+    }
+
+    /// Registers a [zeroizing](Self::of_zeroizing), [redacted](Self::redacted) vtable for [T],
+    /// also redacting the type in the registry's diagnostics.
+    ///
+    /// [Registry]: vtable::Registry
+    #[cfg(feature = "zeroize")]
+    pub fn register_zeroizing<T>(registry: &vtable::Registry) -> Token<T>
+    where
+        T: Clone + Debug + Eq + Hash + ::zeroize::Zeroize + 'static,
+    {
+        registry.redact::<T>();
+        registry.register(Self::of_zeroizing::<T>().redacted())
+    }
+
+    /// Substitutes the equality thunk, e.g. to apply a domain-specific notion of equality.
+    pub const fn with_partial_eq(self, partial_eq: PartialEqFn) -> Self {
+        Self { partial_eq, ..self }
+    }
+
+    /// Omits the value's [TypeId] from its hash, which otherwise distinguishes equal-looking
+    /// values of different types (e.g., `42i32` and `42u32`). Required where the equality thunk
+    /// deems values of different types equal, as those must then hash alike, and should be
+    /// applied to the vtables of all such types.
+    pub const fn without_type_hash(self) -> Self {
+        Self {
+            hash_type: false,
+            ..self
+        }
+    }
+
+    /// Attaches a serialization thunk, typically [`serialize::<T>`](crate::serialize), with which
+    /// a [CHED] is serialized.
+    #[cfg(feature = "serde")]
+    pub const fn with_serialize(self, serialize: crate::SerializeFn) -> Self {
+        Self {
+            serialize: Some(serialize),
+            ..self
+        }
+    }
+
+    /// Attaches a thunk reporting the heap memory owned by a value, typically
+    /// [`deep_size::<T>`](crate::deep_size) for types implementing
+    /// [DeepSize](crate::size::DeepSize).
+    pub const fn with_deep_size(self, deep_size: DeepSizeFn) -> Self {
+        Self {
+            deep_size: Some(deep_size),
+            ..self
+        }
+    }
+
+    /// Attaches a thunk reporting the differences between two values, typically
+    /// [`diff::<T>`](crate::diff()) for types implementing [DynDiff](crate::diff::DynDiff). Without
+    /// one, differences are found by comparing [Debug] renderings.
+    pub const fn with_diff(self, diff: DiffFn) -> Self {
+        Self {
+            diff: Some(diff),
+            ..self
+        }
+    }
+
+    /// Attaches a hook invoked with the value just before it is dropped by the dynamic object
+    /// owning it: a [CHED], [ThinCHED] or [SendCHED], or the last [ArcCHED] or [FrozenCHED]
+    /// sharing it (including those held by an [Interner]). The hook travels with the value when
+    /// it is converted from one dynamic object into another, e.g. by [`CHED::freeze()`]. It is
+    /// not invoked when the value is moved out of the dynamic objects altogether, e.g. by
+    /// `downcast()`, `into_inner()`, [`CHED::into_any()`] or a conversion into a
+    /// `Box<dyn Every>`, whereupon the value is the recipient's to drop.
+    ///
+    /// Typically applied to a vtable that is then registered with
+    /// [`Registry::register()`](vtable::Registry::register), overriding the type's default.
+    pub const fn with_drop_hook(self, on_drop: DropHookFn) -> Self {
+        Self {
+            on_drop: Some(on_drop),
+            ..self
+        }
+    }
+
+    #[inline]
+    fn hash_value(&self, value: &dyn Every, mut state: &mut dyn Hasher) {
+        if self.hash_type {
+            value.type_id().hash(&mut state);
+        }
+        (self.hash)(value, state);
+    }
+}
+
+impl<T: Clone + Debug + Eq + Hash + 'static> Specialise<T> for VTable {
+    fn specialise() -> Self {
+        Self::of::<T>()
+    }
+
+    fn debug_fn() -> Option<DebugFn> {
+        Some(debug::<T>)
+    }
+}
+
+/// Exported thunks resolve the vtable of the exporting copy with
+/// [global_token](vtable::global_token), and adopted thunks the published vtable with
+/// [published](vtable::published). Equality is shared for values of the same type only; values
+/// of different types are deemed unequal by an adopted vtable.
+#[cfg(feature = "shared")]
+impl<T: Clone + Debug + Eq + Hash + 'static> vtable::Share<T> for VTable {
+    fn export() -> vtable_shared::SharedVTable {
+        fn local<T: Clone + Debug + Eq + Hash + 'static>() -> &'static VTable {
+            vtable::global_token::<T, VTable>().vtable_ref()
+        }
+
+        // SAFETY (all thunks): callers pass pointers to values of the type identified by
+        // `type_id`, i.e. T
+        vtable_shared::SharedVTable {
+            type_id: TypeId::of::<T>(),
+            debug: |this, f| (local::<T>().debug)(unsafe { &*this.cast::<T>() }, f),
+            display: |this, f| (local::<T>().display)(unsafe { &*this.cast::<T>() }, f),
+            eq: |this, other| unsafe {
+                (local::<T>().partial_eq)(&*this.cast::<T>(), &*other.cast::<T>())
+            },
+            hash: |this, state| (local::<T>().hash)(unsafe { &*this.cast::<T>() }, state),
+            hash_type: local::<T>().hash_type,
+            drop_hook: |this| {
+                if let Some(on_drop) = local::<T>().on_drop {
+                    on_drop(unsafe { &mut *this.cast::<T>() });
+                }
+            },
+        }
+    }
+
+    fn adopt() -> Self {
+        fn shared<T: 'static>() -> &'static vtable_shared::SharedVTable {
+            vtable::published::<T, VTable>()
+        }
+
+        fn erased<T: 'static>(this: &dyn Every) -> *const () {
+            ptr::from_ref(this.downcast_ref::<T>().unwrap_or_else(panic)).cast()
+        }
+
+        fn erased_mut<T: 'static>(this: &mut dyn Every) -> *mut () {
+            ptr::from_mut(this.downcast_mut::<T>().unwrap_or_else(panic)).cast()
+        }
+
+        // SAFETY (all thunks): the published vtable was verified to be for T, and the pointers
+        // are to values of T
+        Self {
+            debug: |this, f| unsafe { (shared::<T>().debug)(erased::<T>(this), f) },
+            display: |this, f| unsafe { (shared::<T>().display)(erased::<T>(this), f) },
+            partial_eq: |this, other| {
+                other.is::<T>()
+                    && unsafe { (shared::<T>().eq)(erased::<T>(this), erased::<T>(other)) }
+            },
+            hash: |this, state| unsafe { (shared::<T>().hash)(erased::<T>(this), state) },
+            hash_type: shared::<T>().hash_type,
+            on_drop: Some(|this| unsafe { (shared::<T>().drop_hook)(erased_mut::<T>(this)) }),
+            ..Self::of::<T>()
+        }
+    }
+}
+
+impl<T: 'static> Token<T> {
+    /// Creates a token from a `static` vtable, verifying that it was constructed for [T]. Unlike
+    /// [`Token::from_static()`](vtable::Token::from_static), it does not require [VTable] to be
+    /// [specialisable](Specialise) for [T], admitting e.g. [`VTable::of_try_clone()`] vtables.
+    ///
+    /// # Panics
+    /// If the vtable was constructed for a type other than [T].
+    pub fn from_static_checked(vtable: &'static VTable) -> Self {
+        assert!(
+            (vtable.type_id)() == TypeId::of::<T>(),
+            "vtable was not constructed for {}",
+            any::type_name::<T>()
+        );
+        Self::create_unchecked(vtable)
+    }
+}
+
+impl Debug for CHED {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable.debug)(self.inner(), f)
+    }
+}
+
+impl Display for CHED {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable.display)(self.inner(), f)
+    }
+}
+
+impl Clone for CHED {
+    #[inline]
+    fn clone(&self) -> Self {
+        let storage = match &self.storage {
+            Storage::Boxed(inner) => Storage::Boxed((self.vtable.clone)(&**inner)),
+            Storage::Inline(_) => {
+                let mut buf = InlineBuf::uninit();
+                // SAFETY: an inline value is only stored with a vtable specialised for its type
+                unsafe { (self.vtable.clone_into)(self.inner(), buf.as_mut_ptr().cast()) };
+                Storage::Inline(buf)
+            }
+            Storage::Pooled(value) => {
+                // SAFETY: a pooled value is only stored with a vtable specialised for its type,
+                // the new slot being allocated for the same type
+                unsafe {
+                    let slot = Pool::allocate_like(value.cast());
+                    (self.vtable.clone_into)(self.inner(), slot.as_ptr().cast());
+                    let value = (self.vtable.erase)(slot.as_ptr().cast());
+                    Storage::Pooled(NonNull::new_unchecked(value))
+                }
+            }
+        };
+        Self {
+            storage,
+            vtable: self.vtable,
+        }
+    }
+}
+
+impl PartialEq for CHED {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        (self.vtable.partial_eq)(self.inner(), other.inner())
+    }
+}
+
+impl Eq for CHED {}
+
+impl PartialEq<&dyn Every> for CHED {
+    #[inline]
+    fn eq(&self, other: &&dyn Every) -> bool {
+        (self.vtable.partial_eq)(self.inner(), *other)
+    }
+}
+
+impl Hash for CHED {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.vtable.hash_value(self.inner(), state);
+    }
+}
+
+/// With the `serde` feature enabled, a [CHED] is serialized as a `(type name, value)` tuple,
+/// provided its vtable carries a serialization thunk attached with [`VTable::with_serialize()`].
+/// It is deserialized polymorphically from the same form, given a deserializer registered with
+/// [`factory::register_deserialize()`](crate::factory::register_deserialize) and a vtable
+/// specialised in the singleton [Registry](vtable::Registry) for the named type.
+#[cfg(feature = "serde")]
+impl serde::Serialize for CHED {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeTuple};
+
+        let serialize = self.vtable.serialize.ok_or_else(|| {
+            S::Error::custom(format!("no serialization thunk for {}", self.type_name()))
+        })?;
+        let content = serialize(self.inner()).map_err(S::Error::custom)?;
+        let mut tuple = serializer.serialize_tuple(2)?;
+        tuple.serialize_element(self.type_name())?;
+        tuple.serialize_element(&content)?;
+        tuple.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CHED {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let (type_name, content) = <(String, crate::content::Content)>::deserialize(deserializer)?;
+        crate::factory::deserialize_ched_by_name(&type_name, content).map_err(D::Error::custom)
+    }
+}
+
+/// A [CHED] whose value is known to be of type [T], verified once on construction, for hot
+/// paths that would otherwise repeat the [TypeId] check and the dynamic dispatch of every access.
+pub struct Typed<T> {
+    ched: CHED,
+    value_type: PhantomData<T>,
+}
+
+impl<T: 'static> Typed<T> {
+    #[inline]
+    pub fn new(value: T, tok: &Token<T>) -> Self {
+        Self {
+            ched: CHED::new(value, tok),
+            value_type: PhantomData,
+        }
+    }
+
+    /// Views a [CHED] as holding a [T], handing it back if it holds a value of another type.
+    #[inline]
+    pub fn from_ched(ched: CHED) -> Result<Self, CHED> {
+        if ched.is::<T>() {
+            Ok(Self {
+                ched,
+                value_type: PhantomData,
+            })
+        } else {
+            Err(ched)
+        }
+    }
+
+    /// Views a [CHED] as holding a [T] without verifying it.
+    ///
+    /// # Safety
+    /// The value must be of type [T].
+    #[inline]
+    pub unsafe fn from_ched_unchecked(ched: CHED) -> Self {
+        Self {
+            ched,
+            value_type: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn as_ched(&self) -> &CHED {
+        &self.ched
+    }
+
+    #[inline]
+    pub fn into_ched(self) -> CHED {
+        self.ched
+    }
+
+    #[inline]
+    pub fn into_value(self) -> T {
+        self.ched.downcast().unwrap_or_else(|_| unreachable!())
+    }
+
+    #[inline]
+    fn value_ptr(&self) -> *const T {
+        match &self.ched.storage {
+            Storage::Boxed(inner) => (&**inner as *const dyn Every).cast(),
+            Storage::Inline(buf) => buf.as_ptr().cast(),
+            Storage::Pooled(value) => value.as_ptr().cast_const().cast(),
+        }
+    }
+
+    #[inline]
+    fn value_mut_ptr(&mut self) -> *mut T {
+        match &mut self.ched.storage {
+            Storage::Boxed(inner) => (&mut **inner as *mut dyn Every).cast(),
+            Storage::Inline(buf) => buf.as_mut_ptr().cast(),
+            Storage::Pooled(value) => value.as_ptr().cast(),
+        }
+    }
+}
+
+impl<T: 'static> Deref for Typed<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        // SAFETY: the value was verified to be of type T on construction
+        unsafe { &*self.value_ptr() }
+    }
+}
+
+impl<T: 'static> DerefMut for Typed<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: as above, with the value exclusively borrowed through the CHED
+        unsafe { &mut *self.value_mut_ptr() }
+    }
+}
+
+impl<T> From<Typed<T>> for CHED {
+    #[inline]
+    fn from(value: Typed<T>) -> Self {
+        value.ched
+    }
+}
+
+impl<T> Clone for Typed<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            ched: self.ched.clone(),
+            value_type: PhantomData,
+        }
+    }
+}
+
+impl<T> Debug for Typed<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.ched, f)
+    }
+}
+
+/// A [CHED] that memoizes the hash of its value, sparing large values from being rehashed on
+/// every insertion into, or lookup in, a hashed collection. The memoized hash is computed once
+/// with a per-process random seed and invalidated by mutable access.
+///
+/// Equal values hash alike among [HashedCHED] instances, but not with a plain [CHED] or a
+/// [ChedLookup] key, so the two should not be mixed in a collection. As the memo only ever caches
+/// the hash of the current value, Clippy's `mutable_key_type` lint is a false positive here.
+#[derive(Clone)]
+pub struct HashedCHED {
+    ched: CHED,
+    hash: Cell<Option<u64>>,
+}
+
+impl HashedCHED {
+    #[inline]
+    pub fn new(ched: CHED) -> Self {
+        Self {
+            ched,
+            hash: Cell::new(None),
+        }
+    }
+
+    /// The memoized hash of the value, computed on first use as per [`CHED::hash_code()`].
+    #[inline]
+    pub fn hash_code(&self) -> u64 {
+        match self.hash.get() {
+            Some(hash) => hash,
+            None => {
+                let hash = self.ched.hash_code();
+                self.hash.set(Some(hash));
+                hash
+            }
+        }
+    }
+
+    /// Whether the hash has been computed since the value was last mutably accessed.
+    #[inline]
+    pub fn is_memoized(&self) -> bool {
+        self.hash.get().is_some()
+    }
+
+    /// Mutably borrows the value, invalidating the memoized hash.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut dyn Every {
+        self.hash.set(None);
+        self.ched.inner_mut()
+    }
+
+    /// Mutably downcasts the value, invalidating the memoized hash.
+    #[inline]
+    pub fn downcast_mut<T: 'static>(&mut self) -> Result<&mut T, DowncastError> {
+        self.hash.set(None);
+        self.ched.downcast_mut()
+    }
+
+    #[inline]
+    pub fn into_ched(self) -> CHED {
+        self.ched
+    }
+}
+
+impl From<CHED> for HashedCHED {
+    #[inline]
+    fn from(ched: CHED) -> Self {
+        Self::new(ched)
+    }
+}
+
+impl From<HashedCHED> for CHED {
+    #[inline]
+    fn from(value: HashedCHED) -> Self {
+        value.ched
+    }
+}
+
+impl Deref for HashedCHED {
+    type Target = CHED;
+
+    #[inline]
+    fn deref(&self) -> &CHED {
+        &self.ched
+    }
+}
+
+impl Debug for HashedCHED {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.ched, f)
+    }
+}
+
+impl Display for HashedCHED {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.ched, f)
+    }
+}
+
+impl PartialEq for HashedCHED {
+    /// Compares the memoized hashes before the values, to dismiss unequal values cheaply.
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.hash_code() == other.hash_code() && self.ched == other.ched
+    }
+}
+
+impl Eq for HashedCHED {}
+
+impl Hash for HashedCHED {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash_code());
+    }
+}
+
+/// A [CHED] that counts the occasions on which its value is mutably accessed, so that caches
+/// layered over it may tell whether the value may have changed. Mutable access taken through
+/// [`inner_mut()`](Self::inner_mut), or by a successful [`downcast_mut()`](Self::downcast_mut)
+/// or [`map_mut()`](Self::map_mut), advances the version, whether or not the value is actually
+/// modified.
+#[derive(Clone)]
+pub struct TrackedCHED {
+    ched: CHED,
+    version: u64,
+    clean_version: u64,
+}
+
+impl TrackedCHED {
+    #[inline]
+    pub fn new(ched: CHED) -> Self {
+        Self {
+            ched,
+            version: 0,
+            clean_version: 0,
+        }
+    }
+
+    /// The number of times that mutable access has been taken.
+    #[inline]
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Whether mutable access has been taken since the last [`mark_clean()`](Self::mark_clean),
+    /// or since construction.
+    #[inline]
+    pub fn is_dirty(&self) -> bool {
+        self.version != self.clean_version
+    }
+
+    #[inline]
+    pub fn mark_clean(&mut self) {
+        self.clean_version = self.version;
+    }
+
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut dyn Every {
+        self.version += 1;
+        self.ched.inner_mut()
+    }
+
+    #[inline]
+    pub fn downcast_mut<T: 'static>(&mut self) -> Result<&mut T, DowncastError> {
+        let value = self.ched.downcast_mut()?;
+        self.version += 1;
+        Ok(value)
+    }
+
+    #[inline]
+    pub fn map_mut<T: 'static, R>(
+        &mut self,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Result<R, DowncastError> {
+        self.downcast_mut().map(f)
+    }
+
+    #[inline]
+    pub fn into_ched(self) -> CHED {
+        self.ched
+    }
+}
+
+impl From<CHED> for TrackedCHED {
+    #[inline]
+    fn from(ched: CHED) -> Self {
+        Self::new(ched)
+    }
+}
+
+impl From<TrackedCHED> for CHED {
+    #[inline]
+    fn from(value: TrackedCHED) -> Self {
+        value.ched
+    }
+}
+
+impl Deref for TrackedCHED {
+    type Target = CHED;
+
+    #[inline]
+    fn deref(&self) -> &CHED {
+        &self.ched
+    }
+}
+
+impl Debug for TrackedCHED {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.ched, f)
+    }
+}
+
+impl Display for TrackedCHED {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.ched, f)
+    }
+}
+
+/// The heap block of a [ThinCHED], led by the vtable.
+#[repr(C)]
+struct ThinBlock<T> {
+    vtable: &'static VTable,
+    value: T,
+}
+
+/// A single-pointer counterpart of [CHED], whose vtable reference is held on the heap alongside
+/// the value. Compared with [CHED], it is a third of the size in collections, at the cost of
+/// always allocating, even for the small values that [CHED] stores inline.
+pub struct ThinCHED {
+    block: NonNull<&'static VTable>,
+}
+
+impl ThinCHED {
+    /// # Panics
+    /// If the token's vtable was specialised for a type other than [T], as may be the case for
+    /// one paired with a `static` vtable by [`Token::from_static()`](vtable::Token::from_static).
+    #[inline]
+    pub fn new<T: 'static>(value: T, tok: &Token<T>) -> Self {
+        let vtable = tok.vtable_ref();
+        assert!(
+            (vtable.type_id)() == TypeId::of::<T>(),
+            "vtable was not specialised for {}",
+            any::type_name::<T>()
+        );
+        let layout = Layout::new::<ThinBlock<T>>();
+        // SAFETY: the block is never zero-sized, as it includes the vtable reference
+        let block = unsafe { alloc::alloc(layout) }.cast::<ThinBlock<T>>();
+        let Some(block) = NonNull::new(block) else {
+            alloc::handle_alloc_error(layout);
+        };
+        // SAFETY: the block was allocated for a ThinBlock<T>
+        unsafe { block.write(ThinBlock { vtable, value }) };
+        Self {
+            block: block.cast(),
+        }
+    }
+
+    #[inline]
+    pub fn new_auto<T: Clone + Debug + Eq + Hash + 'static>(value: T) -> Self {
+        Self::new(value, vtable::global_token())
+    }
+
+    #[inline]
+    pub fn vtable(&self) -> &'static VTable {
+        // SAFETY: the block is led by the vtable reference
+        unsafe { *self.block.as_ptr() }
+    }
+
+    #[inline]
+    fn value(&self) -> *mut dyn Every {
+        let vtable = self.vtable();
+        // SAFETY: the offset was recorded for the type the vtable was verified against
+        (vtable.erase)(unsafe { self.block.cast::<u8>().add(vtable.thin_offset) }.as_ptr().cast())
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &dyn Every {
+        // SAFETY: the value is initialised for the lifetime of the block
+        unsafe { &*self.value() }
+    }
+
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut dyn Every {
+        // SAFETY: as per inner(), with exclusive access to the block
+        unsafe { &mut *self.value() }
+    }
+
+    /// Deallocates the block without dropping the value.
+    ///
+    /// # Safety
+    /// The value must have been moved out or dropped in place.
+    #[inline]
+    unsafe fn release(self) {
+        let this = ManuallyDrop::new(self);
+        unsafe { alloc::dealloc(this.block.as_ptr().cast(), this.vtable().thin_layout) };
+    }
+
+    /// Unwraps the erased value, moving it into a [Box].
+    #[inline]
+    pub fn into_inner(self) -> Box<dyn Every> {
+        // SAFETY: the value is moved out before the block is released
+        unsafe {
+            let inner = (self.vtable().take)(self.value().cast());
+            self.release();
+            inner
+        }
+    }
+
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.inner().type_name()
+    }
+
+    #[inline]
+    pub fn is<T: 'static>(&self) -> bool {
+        self.inner().is::<T>()
+    }
+
+    #[inline]
+    pub fn downcast_ref<T: 'static>(&self) -> Result<&T, DowncastError> {
+        self.inner().downcast_ref()
+    }
+
+    #[inline]
+    pub fn downcast_mut<T: 'static>(&mut self) -> Result<&mut T, DowncastError> {
+        self.inner_mut().downcast_mut()
+    }
+
+    #[inline]
+    pub fn downcast<T: 'static>(self) -> Result<T, DowncastError> {
+        self.downcast_ref::<T>()?;
+        // SAFETY: just checked the type; the value is moved out before the block is released
+        unsafe {
+            let value = self.value().cast::<T>().read();
+            self.release();
+            Ok(value)
+        }
+    }
+}
+
+impl Drop for ThinCHED {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(on_drop) = self.vtable().on_drop {
+            on_drop(self.inner_mut());
+        }
+        // SAFETY: the value is dropped exactly once, before the block is deallocated
+        unsafe {
+            ptr::drop_in_place(self.value());
+            alloc::dealloc(self.block.as_ptr().cast(), self.vtable().thin_layout);
+        }
+    }
+}
+
+impl From<ThinCHED> for CHED {
+    #[inline]
+    fn from(value: ThinCHED) -> Self {
+        let vtable = value.vtable();
+        Self::from_parts(value.into_inner(), vtable)
+    }
+}
+
+impl Debug for ThinCHED {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable().debug)(self.inner(), f)
+    }
+}
+
+impl Display for ThinCHED {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable().display)(self.inner(), f)
+    }
+}
+
+impl Clone for ThinCHED {
+    fn clone(&self) -> Self {
+        let vtable = self.vtable();
+        // SAFETY: the block is never zero-sized, as it includes the vtable reference
+        let block = unsafe { alloc::alloc(vtable.thin_layout) }.cast::<&'static VTable>();
+        let Some(block) = NonNull::new(block) else {
+            alloc::handle_alloc_error(vtable.thin_layout);
+        };
+        // SAFETY: the block has the layout of the original, which the vtable was verified against
+        unsafe {
+            block.write(vtable);
+            let value = block.cast::<u8>().add(vtable.thin_offset);
+            (vtable.clone_into)(self.inner(), value.as_ptr().cast());
+        }
+        Self { block }
+    }
+}
+
+impl PartialEq for ThinCHED {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        (self.vtable().partial_eq)(self.inner(), other.inner())
+    }
+}
+
+impl Eq for ThinCHED {}
+
+impl Hash for ThinCHED {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.vtable().hash_value(self.inner(), state);
+    }
+}
+
+/// A counterpart of [CHED] that refers to its [VTable] by a compact
+/// [VTableId](vtable::VTableId) allocated in the singleton [Registry](vtable::Registry), rather
+/// than by a `&'static` reference. The identifier stands in for the value's type where objects are
+/// represented on disk or on the wire, and the vtable is resolved whenever it is needed, at the
+/// cost of a registry lookup per operation.
+pub struct IdCHED {
+    inner: Box<dyn Every>,
+    id: vtable::VTableId,
+}
+
+impl IdCHED {
+    #[inline]
+    pub fn new<T: Clone + Debug + Eq + Hash + 'static>(value: T) -> Self {
+        Self {
+            inner: Box::new(value),
+            id: vtable::Registry::singleton().id::<T, VTable>(),
+        }
+    }
+
+    #[inline]
+    pub fn id(&self) -> vtable::VTableId {
+        self.id
+    }
+
+    /// Resolves the vtable, provided the identifier still refers to one specialised for the
+    /// value's type.
+    fn try_vtable(&self) -> Option<&'static VTable> {
+        let vtable = vtable::Registry::singleton().resolve_id::<VTable>(self.id)?;
+        ((vtable.type_id)() == (*self.inner).type_id()).then_some(vtable)
+    }
+
+    /// # Panics
+    /// If the identifier no longer resolves to a vtable for the value's type, as may be the case
+    /// once the entry has been overridden with a mismatched vtable, or on a thread whose singleton
+    /// has since been isolated with `Registry::isolate()`.
+    pub fn vtable(&self) -> &'static VTable {
+        self.try_vtable().unwrap_or_else(|| {
+            panic!("{:?} does not identify a vtable for {}", self.id, self.type_name())
+        })
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &dyn Every {
+        &*self.inner
+    }
+
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut dyn Every {
+        &mut *self.inner
+    }
+
+    /// Unwraps the erased value, which is thereafter dropped without the drop hook.
+    #[inline]
+    pub fn into_inner(self) -> Box<dyn Every> {
+        let this = ManuallyDrop::new(self);
+        // SAFETY: the box is read exactly once and the original is not dropped
+        unsafe { ptr::read(&this.inner) }
+    }
+
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.inner.type_name()
+    }
+
+    #[inline]
+    pub fn is<T: 'static>(&self) -> bool {
+        self.inner.is::<T>()
+    }
+
+    #[inline]
+    pub fn downcast_ref<T: 'static>(&self) -> Result<&T, DowncastError> {
+        self.inner.downcast_ref()
+    }
+
+    #[inline]
+    pub fn downcast_mut<T: 'static>(&mut self) -> Result<&mut T, DowncastError> {
+        self.inner.downcast_mut()
+    }
+
+    #[inline]
+    pub fn downcast<T: 'static>(self) -> Result<T, DowncastError> {
+        self.into_inner().downcast()
+    }
+}
+
+impl Drop for IdCHED {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(on_drop) = self.try_vtable().and_then(|vtable| vtable.on_drop) {
+            on_drop(&mut *self.inner);
+        }
+    }
+}
+
+impl From<IdCHED> for CHED {
+    #[inline]
+    fn from(value: IdCHED) -> Self {
+        let vtable = value.vtable();
+        Self::from_parts(value.into_inner(), vtable)
+    }
+}
+
+impl Debug for IdCHED {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable().debug)(self.inner(), f)
+    }
+}
+
+impl Display for IdCHED {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable().display)(self.inner(), f)
+    }
+}
+
+impl Clone for IdCHED {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            inner: (self.vtable().clone)(self.inner()),
+            id: self.id,
+        }
+    }
+}
+
+impl PartialEq for IdCHED {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        (self.vtable().partial_eq)(self.inner(), other.inner())
+    }
+}
+
+impl Eq for IdCHED {}
+
+impl Hash for IdCHED {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.vtable().hash_value(self.inner(), state);
+    }
+}
+
+/// A borrowed stand-in for a [CHED], for looking up `HashMap<CHED, V>` (and similar) by a plain
+/// value without boxing a temporary [CHED]. It hashes and compares the value through a vtable
+/// in the same way as [CHED], and is passed to lookups as a `&dyn` [ChedLookup], e.g.
+/// `map.get(&ChedKey::new_auto(&42) as &dyn ChedLookup)`.
+#[derive(Clone, Copy)]
+pub struct ChedKey<'a> {
+    value: &'a dyn Every,
+    vtable: &'static VTable,
+}
+
+impl<'a> ChedKey<'a> {
+    #[inline]
+    pub fn new<T: 'static>(value: &'a T, tok: &Token<T>) -> Self {
+        Self {
+            value,
+            vtable: tok.vtable_ref(),
+        }
+    }
+
+    #[inline]
+    pub fn new_auto<T: Clone + Debug + Eq + Hash + 'static>(value: &'a T) -> Self {
+        Self::new(value, vtable::global_token())
+    }
+}
+
+/// Unifies [CHED] and [ChedKey] as the borrowed form of a [CHED] map key.
+pub trait ChedLookup {
+    fn ched_key(&self) -> ChedKey<'_>;
+}
+
+impl ChedLookup for CHED {
+    #[inline]
+    fn ched_key(&self) -> ChedKey<'_> {
+        ChedKey {
+            value: self.inner(),
+            vtable: self.vtable,
+        }
+    }
+}
+
+impl ChedLookup for ChedKey<'_> {
+    #[inline]
+    fn ched_key(&self) -> ChedKey<'_> {
+        *self
+    }
+}
+
+impl<'a> Borrow<dyn ChedLookup + 'a> for CHED {
+    #[inline]
+    fn borrow(&self) -> &(dyn ChedLookup + 'a) {
+        self
+    }
+}
+
+impl PartialEq for dyn ChedLookup + '_ {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        let (lhs, rhs) = (self.ched_key(), other.ched_key());
+        (lhs.vtable.partial_eq)(lhs.value, rhs.value)
+    }
+}
+
+impl Eq for dyn ChedLookup + '_ {}
+
+impl Hash for dyn ChedLookup + '_ {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let key = self.ched_key();
+        key.vtable.hash_value(key.value, state);
+    }
+}
+
+/// A borrowed, non-owning counterpart of [CHED], for comparing, hashing and formatting a value
+/// dynamically without boxing it.
+#[derive(Clone, Copy)]
+pub struct ChedRef<'a> {
+    value: &'a dyn Every,
+    vtable: &'static VTable,
+}
+
+impl<'a> ChedRef<'a> {
+    #[inline]
+    pub fn new<T: 'static>(value: &'a T, tok: &Token<T>) -> Self {
+        Self {
+            value,
+            vtable: tok.vtable_ref(),
+        }
+    }
+
+    #[inline]
+    pub fn new_auto<T: Clone + Debug + Eq + Hash + 'static>(value: &'a T) -> Self {
+        Self::new(value, vtable::global_token())
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &'a dyn Every {
+        self.value
+    }
+
+    #[inline]
+    pub fn vtable(&self) -> &'static VTable {
+        self.vtable
+    }
+
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.value.type_name()
+    }
+
+    #[inline]
+    pub fn downcast_ref<T: 'static>(&self) -> Result<&'a T, DowncastError> {
+        self.value.downcast_ref()
+    }
+
+    /// Whether both borrow the same value in memory, as per [`CHED::ptr_eq()`].
+    #[inline]
+    pub fn ptr_eq(&self, other: &ChedRef<'_>) -> bool {
+        ptr::addr_eq(self.value, other.value)
+    }
+
+    /// Clones the borrowed value into an owned [CHED].
+    #[inline]
+    pub fn to_ched(&self) -> CHED {
+        CHED::from_parts((self.vtable.clone)(self.value), self.vtable)
+    }
+}
+
+impl CHED {
+    /// Borrows the [CHED] as a [ChedRef].
+    #[inline]
+    pub fn as_ched_ref(&self) -> ChedRef<'_> {
+        ChedRef {
+            value: self.inner(),
+            vtable: self.vtable,
+        }
+    }
+}
+
+impl ChedLookup for ChedRef<'_> {
+    #[inline]
+    fn ched_key(&self) -> ChedKey<'_> {
+        ChedKey {
+            value: self.value,
+            vtable: self.vtable,
+        }
+    }
+}
+
+impl Debug for ChedRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable.debug)(self.value, f)
+    }
+}
+
+impl Display for ChedRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable.display)(self.value, f)
+    }
+}
+
+impl PartialEq for ChedRef<'_> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        (self.vtable.partial_eq)(self.value, other.value)
+    }
+}
+
+impl Eq for ChedRef<'_> {}
+
+impl PartialEq<CHED> for ChedRef<'_> {
+    #[inline]
+    fn eq(&self, other: &CHED) -> bool {
+        (self.vtable.partial_eq)(self.value, other.inner())
+    }
+}
+
+impl Hash for ChedRef<'_> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.vtable.hash_value(self.value, state);
+    }
+}
+
+/// A thread-safe counterpart of [CHED], constructible only from `Send + Sync` values and thus
+/// itself `Send + Sync`. It shares the [VTable] (and its [Token]) with [CHED].
+pub struct SendCHED {
+    inner: Box<dyn Every + Send + Sync>,
+    vtable: &'static VTable,
+}
+
+impl SendCHED {
+    #[inline]
+    pub fn new<T: Send + Sync + 'static>(value: T, tok: &Token<T>) -> Self {
+        Self {
+            inner: Box::new(value),
+            vtable: tok.vtable_ref(),
+        }
+    }
+
+    #[inline]
+    pub fn new_auto<T: Clone + Debug + Eq + Hash + Send + Sync + 'static>(value: T) -> Self {
+        Self::new(value, vtable::global_token())
+    }
+
+    #[inline]
+    #[allow(clippy::borrowed_box)]
+    pub fn inner(&self) -> &Box<dyn Every + Send + Sync> {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut Box<dyn Every + Send + Sync> {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Box<dyn Every + Send + Sync> {
+        self.into_parts().0
+    }
+
+    /// Disassembles the [SendCHED] without invoking the drop hook.
+    #[inline]
+    fn into_parts(self) -> (Box<dyn Every + Send + Sync>, &'static VTable) {
+        let this = ManuallyDrop::new(self);
+        // SAFETY: the box is read exactly once and the original is not dropped
+        (unsafe { ptr::read(&this.inner) }, this.vtable)
+    }
+
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        (*self.inner).type_name()
+    }
+
+    #[inline]
+    pub fn type_id(&self) -> TypeId {
+        (*self.inner).type_id()
+    }
+
+    #[inline]
+    pub fn is<T: 'static>(&self) -> bool {
+        self.inner.is::<T>()
+    }
+
+    #[inline]
+    pub fn downcast_ref<T: 'static>(&self) -> Result<&T, DowncastError> {
+        self.inner.downcast_ref()
+    }
+
+    #[inline]
+    pub fn downcast_mut<T: 'static>(&mut self) -> Result<&mut T, DowncastError> {
+        self.inner.downcast_mut()
+    }
+
+    #[inline]
+    pub fn downcast<T: 'static>(self) -> Result<T, DowncastError> {
+        self.into_inner().downcast()
+    }
+}
+
+impl Drop for SendCHED {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(on_drop) = self.vtable.on_drop {
+            on_drop(&mut *self.inner);
+        }
+    }
+}
+
+impl From<SendCHED> for CHED {
+    #[inline]
+    fn from(value: SendCHED) -> Self {
+        let (inner, vtable) = value.into_parts();
+        Self::from_parts(inner, vtable)
+    }
+}
+
+impl Debug for SendCHED {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable.debug)(&*self.inner, f)
+    }
+}
+
+impl Display for SendCHED {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable.display)(&*self.inner, f)
+    }
+}
+
+impl Clone for SendCHED {
+    #[inline]
+    fn clone(&self) -> Self {
+        let cloned = (self.vtable.clone)(&*self.inner);
+        Self {
+            // SAFETY: the clone thunk cannot be substituted, so the clone is of the original
+            // Send + Sync type; auto traits do not alter the layout of the box or its vtable
+            inner: unsafe {
+                mem::transmute::<Box<dyn Every>, Box<dyn Every + Send + Sync>>(cloned)
+            },
+            vtable: self.vtable,
+        }
+    }
+}
+
+impl PartialEq for SendCHED {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        (self.vtable.partial_eq)(&*self.inner, &*other.inner)
+    }
+}
+
+impl Eq for SendCHED {}
+
+impl Hash for SendCHED {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.vtable.hash_value(&*self.inner, state);
+    }
+}
+
+/// A shared counterpart of [CHED], whose [Clone] merely increments a reference count. Equality,
+/// hashing and formatting go through the same [VTable] as [CHED].
+///
+/// Value semantics are preserved under mutation by copy-on-write: [`inner_mut()`](Self::inner_mut)
+/// and [`downcast_mut()`](Self::downcast_mut) deep-clone the value first if it is shared.
+pub struct ArcCHED {
+    inner: Arc<dyn Every>,
+    vtable: &'static VTable,
+}
+
+impl ArcCHED {
+    #[inline]
+    pub fn new<T: 'static>(value: T, tok: &Token<T>) -> Self {
+        Self {
+            inner: Arc::new(value),
+            vtable: tok.vtable_ref(),
+        }
+    }
+
+    #[inline]
+    pub fn new_auto<T: Clone + Debug + Eq + Hash + 'static>(value: T) -> Self {
+        Self::new(value, vtable::global_token())
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &Arc<dyn Every> {
+        &self.inner
+    }
+
+    /// Surrenders the shared value, which is thereafter dropped without the drop hook.
+    #[inline]
+    pub fn into_inner(self) -> Arc<dyn Every> {
+        let this = ManuallyDrop::new(self);
+        // SAFETY: the reference is read exactly once and the original is not dropped
+        unsafe { ptr::read(&this.inner) }
+    }
+
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        (*self.inner).type_name()
+    }
+
+    #[inline]
+    pub fn type_id(&self) -> TypeId {
+        (*self.inner).type_id()
+    }
+
+    #[inline]
+    pub fn is<T: 'static>(&self) -> bool {
+        self.inner.is::<T>()
+    }
+
+    #[inline]
+    pub fn downcast_ref<T: 'static>(&self) -> Result<&T, DowncastError> {
+        self.inner.downcast_ref()
+    }
+
+    /// Mutably borrows the value, first deep-cloning it if other [ArcCHED] instances share it.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut dyn Every {
+        if Arc::get_mut(&mut self.inner).is_none() {
+            self.inner = Arc::from((self.vtable.clone)(&*self.inner));
+        }
+        Arc::get_mut(&mut self.inner).unwrap()
+    }
+
+    /// Mutably downcasts the value, copying it on write as per [`inner_mut()`](Self::inner_mut).
+    /// The value is not copied if the downcast fails.
+    #[inline]
+    pub fn downcast_mut<T: 'static>(&mut self) -> Result<&mut T, DowncastError> {
+        self.inner.downcast_ref::<T>()?;
+        self.inner_mut().downcast_mut()
+    }
+
+    /// Whether the value is shared with other [ArcCHED] instances, such that mutating it would
+    /// incur a copy.
+    #[inline]
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.inner) > 1 || Arc::weak_count(&self.inner) > 0
+    }
+
+    /// Whether both share the same allocation, in which case they are necessarily equal.
+    #[inline]
+    pub fn ptr_eq(&self, other: &ArcCHED) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Deep-clones the shared value into an exclusively owned [CHED].
+    #[inline]
+    pub fn to_owned_ched(&self) -> CHED {
+        CHED::from_parts((self.vtable.clone)(&*self.inner), self.vtable)
+    }
+}
+
+impl Drop for ArcCHED {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(on_drop) = self.vtable.on_drop
+            && let Some(value) = Arc::get_mut(&mut self.inner)
+        {
+            on_drop(value);
+        }
+    }
+}
+
+impl From<CHED> for ArcCHED {
+    #[inline]
+    fn from(value: CHED) -> Self {
+        let vtable = value.vtable;
+        Self {
+            inner: Arc::from(value.into_inner()),
+            vtable,
+        }
+    }
+}
+
+impl Debug for ArcCHED {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable.debug)(&*self.inner, f)
+    }
+}
+
+impl Display for ArcCHED {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable.display)(&*self.inner, f)
+    }
+}
+
+impl Clone for ArcCHED {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            vtable: self.vtable,
+        }
+    }
+}
+
+impl PartialEq for ArcCHED {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        (self.vtable.partial_eq)(&*self.inner, &*other.inner)
+    }
+}
+
+impl Eq for ArcCHED {}
+
+impl Hash for ArcCHED {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.vtable.hash_value(&*self.inner, state);
+    }
+}
+
+impl ChedLookup for ArcCHED {
+    #[inline]
+    fn ched_key(&self) -> ChedKey<'_> {
+        ChedKey {
+            value: &*self.inner,
+            vtable: self.vtable,
+        }
+    }
+}
+
+impl<'a> Borrow<dyn ChedLookup + 'a> for ArcCHED {
+    #[inline]
+    fn borrow(&self) -> &(dyn ChedLookup + 'a) {
+        self
+    }
+}
+
+/// An erased form in which a [FrozenCHED] may hold its value: `dyn Every` or, to make the
+/// [FrozenCHED] [Send] and [Sync], `dyn Every + Send + Sync`.
+pub trait Erased {
+    fn as_every(&self) -> &dyn Every;
+
+    fn as_every_mut(&mut self) -> &mut dyn Every;
+}
+
+impl Erased for dyn Every {
+    #[inline]
+    fn as_every(&self) -> &dyn Every {
+        self
+    }
+
+    #[inline]
+    fn as_every_mut(&mut self) -> &mut dyn Every {
+        self
+    }
+}
+
+impl Erased for dyn Every + Send + Sync {
+    #[inline]
+    fn as_every(&self) -> &dyn Every {
+        self
+    }
+
+    #[inline]
+    fn as_every_mut(&mut self) -> &mut dyn Every {
+        self
+    }
+}
+
+/// An immutable dynamic object, produced by [`CHED::freeze()`] or [`SendCHED::freeze()`], for
+/// publishing into shared, read-only structures. Offering no mutable access, it is cloned by
+/// reference counting rather than by copying the value, and is [Sync] if frozen from a
+/// [SendCHED].
+pub struct FrozenCHED<E: ?Sized + Erased = dyn Every> {
+    inner: Arc<E>,
+    vtable: &'static VTable,
+}
+
+impl CHED {
+    /// Freezes the [CHED] into an immutable [FrozenCHED], moving the value once into a shared
+    /// allocation.
+    #[inline]
+    pub fn freeze(self) -> FrozenCHED {
+        let vtable = self.vtable;
+        FrozenCHED {
+            inner: Arc::from(self.into_inner()),
+            vtable,
+        }
+    }
+}
+
+impl SendCHED {
+    /// Freezes the [SendCHED] into an immutable [FrozenCHED] that may be shared across threads.
+    #[inline]
+    pub fn freeze(self) -> FrozenCHED<dyn Every + Send + Sync> {
+        let (inner, vtable) = self.into_parts();
+        FrozenCHED {
+            inner: Arc::from(inner),
+            vtable,
+        }
+    }
+}
+
+impl<E: ?Sized + Erased> FrozenCHED<E> {
+    #[inline]
+    pub fn inner(&self) -> &dyn Every {
+        self.inner.as_every()
+    }
+
+    #[inline]
+    pub fn vtable(&self) -> &'static VTable {
+        self.vtable
+    }
+
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.inner().type_name()
+    }
+
+    #[inline]
+    pub fn type_id(&self) -> TypeId {
+        self.inner().type_id()
+    }
+
+    #[inline]
+    pub fn is<T: 'static>(&self) -> bool {
+        self.inner().is::<T>()
+    }
+
+    #[inline]
+    pub fn downcast_ref<T: 'static>(&self) -> Result<&T, DowncastError> {
+        self.inner().downcast_ref()
+    }
+
+    /// Whether both share the same allocation, in which case they are necessarily equal.
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Deep-clones the value into a mutable [CHED].
+    #[inline]
+    pub fn thaw(&self) -> CHED {
+        CHED::from_parts((self.vtable.clone)(self.inner()), self.vtable)
+    }
+}
+
+impl<E: ?Sized + Erased> Drop for FrozenCHED<E> {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(on_drop) = self.vtable.on_drop
+            && let Some(value) = Arc::get_mut(&mut self.inner)
+        {
+            on_drop(value.as_every_mut());
+        }
+    }
+}
+
+impl<E: ?Sized + Erased> Clone for FrozenCHED<E> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            vtable: self.vtable,
+        }
+    }
+}
+
+impl<E: ?Sized + Erased> Debug for FrozenCHED<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable.debug)(self.inner(), f)
+    }
+}
+
+impl<E: ?Sized + Erased> Display for FrozenCHED<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable.display)(self.inner(), f)
+    }
+}
+
+impl<E: ?Sized + Erased> PartialEq for FrozenCHED<E> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || (self.vtable.partial_eq)(self.inner(), other.inner())
+    }
+}
+
+impl<E: ?Sized + Erased> Eq for FrozenCHED<E> {}
+
+impl<E: ?Sized + Erased> Hash for FrozenCHED<E> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.vtable.hash_value(self.inner(), state);
+    }
+}
+
+/// De-duplicates values, such that equal values (as per their vtables) share a single allocation
+/// behind cheap [Interned] handles.
+#[derive(Default)]
+pub struct Interner {
+    values: RefCell<HashSet<ArcCHED>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Obtains the handle to the interned value equal to the given one, interning the value if
+    /// there is none.
+    pub fn intern<T: 'static>(&self, value: T, tok: &Token<T>) -> Interned {
+        let key = ChedKey::new(&value, tok);
+        let existing = self.values.borrow().get(&key as &dyn ChedLookup).cloned();
+        Interned(existing.unwrap_or_else(|| {
+            let interned = ArcCHED::new(value, tok);
+            self.values.borrow_mut().insert(interned.clone());
+            interned
+        }))
+    }
+
+    /// As per [`intern()`](Self::intern), for an already erased value.
+    pub fn intern_ched(&self, value: CHED) -> Interned {
+        let existing = self.values.borrow().get(&value as &dyn ChedLookup).cloned();
+        Interned(existing.unwrap_or_else(|| {
+            let interned = ArcCHED::from(value);
+            self.values.borrow_mut().insert(interned.clone());
+            interned
+        }))
+    }
+
+    /// The number of distinct values interned.
+    pub fn len(&self) -> usize {
+        self.values.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.borrow().is_empty()
+    }
+
+    /// Evicts the values to which no [Interned] handles remain.
+    pub fn purge(&self) {
+        self.values.borrow_mut().retain(ArcCHED::is_shared);
+    }
+}
+
+/// A handle to a value in an [Interner]. Cloning and comparing handles are pointer operations:
+/// handles from the same [Interner] are equal if and only if their values are.
+#[derive(Clone)]
+pub struct Interned(ArcCHED);
+
+impl Deref for Interned {
+    type Target = ArcCHED;
+
+    #[inline]
+    fn deref(&self) -> &ArcCHED {
+        &self.0
+    }
+}
+
+impl Debug for Interned {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl Display for Interned {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl PartialEq for Interned {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ptr_eq(&other.0)
+    }
+}
+
+impl Eq for Interned {}
+
+impl Hash for Interned {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0.inner).cast::<()>().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::every::{panic, BoxDowncast, DowncastFilter, Every};
+    use crate::pool::Pool;
+    use crate::vtable::{Registry, Token};
+    use crate::ched::{
+        ArcCHED, ChedKey, ChedLookup, ChedRef, FrozenCHED, HashedCHED, IdCHED, Interner, SendCHED,
+        ThinCHED, TrackedCHED, Typed, VTable, CHED, INLINE_CAPACITY,
+    };
+    use std::any::TypeId;
+    use std::collections::HashMap;
+    use std::hash::{DefaultHasher, Hash, Hasher};
+    use std::mem::size_of;
+    use std::fmt::Formatter;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[test]
+    fn self_is_equal() {
+        let obj = CHED::new(42, &Token::default());
+        assert_eq!(obj, obj);
+    }
+
+    #[test]
+    fn same_value_equal() {
+        let vtable_tok_i32 = Token::default();
+        let obj_1 = CHED::new(42, &vtable_tok_i32);
+        let obj_2 = CHED::new(42, &vtable_tok_i32);
+        assert_eq!(obj_1, obj_2);
+    }
+
+    #[test]
+    fn cloned_value_equal() {
+        let obj_1 = CHED::new(42, &Token::default());
+        let obj_2 = obj_1.clone();
+        assert_eq!(obj_1, obj_2);
+    }
+
+    #[test]
+    fn different_values_not_equal() {
+        let vtable_tok_i32 = Token::default();
+
+        let obj_1 = CHED::new(42, &vtable_tok_i32);
+        let obj_2 = CHED::new(43, &vtable_tok_i32);
+        assert_ne!(obj_1, obj_2);
+    }
+
+    #[test]
+    fn different_types_not_equal() {
+        let obj_1 = CHED::new(42, &Token::default());
+        let obj_2 = CHED::new("foo", &Token::default());
+        assert_ne!(obj_1, obj_2);
+    }
+
+    #[test]
+    fn test_debug() {
+        let obj = CHED::new(42, &Token::default());
+        let debug = format!("{obj:?}");
+        assert_eq!("42", debug);
+    }
+
+    #[test]
+    fn test_hash() {
+        let vtable_tok_i32 = Token::default();
+        let vtable_tok_str_slice = Token::default();
+
+        let mut map = HashMap::new();
+        assert!(map.insert(CHED::new(42, &vtable_tok_i32), ()).is_none());
+        assert!(map.insert(CHED::new(43, &vtable_tok_i32), ()).is_none());
+        assert!(map
+            .insert(CHED::new("foo", &vtable_tok_str_slice), ())
+            .is_none());
+
+        assert!(map.insert(CHED::new(42, &vtable_tok_i32), ()).is_some());
+        assert!(map.insert(CHED::new(43, &vtable_tok_i32), ()).is_some());
+        assert!(map
+            .insert(CHED::new("foo", &vtable_tok_str_slice), ())
+            .is_some());
+    }
+
+    #[test]
+    fn downcast_ref() {
+        let obj = CHED::new(42i32, &Token::default());
+        assert_eq!(
+            &42i32,
+            obj.inner().downcast_ref::<i32>().unwrap_or_else(panic)
+        );
+    }
+
+    #[test]
+    fn downcast_mut() {
+        let mut obj_1 = CHED::new(42i32, &Token::default());
+        let obj_2 = obj_1.clone();
+        *obj_1.inner_mut().downcast_mut().unwrap() = 13;
+        assert_eq!(
+            &13i32,
+            obj_1.inner().downcast_ref::<i32>().unwrap_or_else(panic)
+        );
+        assert_eq!(
+            &42i32,
+            obj_2.inner().downcast_ref::<i32>().unwrap_or_else(panic)
+        );
+    }
+
+    #[test]
+    fn downcast() {
+        let obj = CHED::new(42i32, &Token::default());
+        assert_eq!(42i32, obj.into_inner().downcast::<i32>().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot downcast i32 (was 42) into u32; did you mean i32?")]
+    fn downcast_ref_with_wrong_type() {
+        let obj = CHED::new(42i32, &Token::default());
+        let _: &u32 = obj.inner().downcast_ref().unwrap_or_else(panic);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot downcast i32 (was 42) into u32; did you mean i32?")]
+    fn downcast_with_wrong_type() {
+        let obj = CHED::new(42i32, &Token::default());
+        let _: u32 = obj.into_inner().downcast().unwrap_or_else(panic);
+    }
+
+    #[test]
+    fn downcast_error_with_debug_snapshot() {
+        let obj = CHED::new(42i32, &Token::default());
+        let err = obj.inner().downcast_ref::<u32>().unwrap_err();
+        assert_eq!(Some("42"), err.source_debug.as_deref());
+        assert_eq!(
+            "cannot downcast i32 (was 42) into u32; did you mean i32?",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn downcast_filter() {
+        let values = vec![
+            CHED::new(1i32, &Token::default()),
+            CHED::new("foo", &Token::default()),
+            CHED::new(2i32, &Token::default()),
+        ];
+        assert_eq!(vec![&"foo"], values.iter().downcast_filter::<&str>().collect::<Vec<_>>());
+        assert_eq!(vec![1, 2], values.into_iter().downcast_filter::<i32>().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn downcast_error_with_suggestion() {
+        let obj = CHED::new(42i32, &Token::default());
+        let err = obj.inner().downcast_ref::<u32>().unwrap_err();
+        assert_eq!(Some("i32"), err.suggestion.as_deref());
+
+        let err = obj.inner().downcast_ref::<String>().unwrap_err();
+        assert_eq!(None, err.suggestion.as_deref());
+    }
+
+    #[test]
+    fn static_vtable() {
+        static VTABLE: VTable = VTable::of::<u64>();
+        static TOKEN: Token<u64, VTable> = Token::from_static(&VTABLE);
+
+        let obj_1 = CHED::new(42u64, &TOKEN);
+        let obj_2 = obj_1.clone();
+        assert_eq!(obj_1, obj_2);
+        assert_eq!("42", format!("{obj_1:?}"));
+    }
+
+    #[test]
+    fn registered_override() {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Celsius(i16);
+
+        fn debug(this: &dyn Every, f: &mut Formatter<'_>) -> std::fmt::Result {
+            let this = this.downcast_ref::<Celsius>().unwrap_or_else(panic);
+            write!(f, "{}°C", this.0)
+        }
+
+        let registry = Registry::new();
+        registry.register::<Celsius, _>(VTable::of::<Celsius>().with_debug(debug));
+        let obj = CHED::new(Celsius(21), &Token::in_registry(&registry));
+        assert_eq!("21°C", format!("{obj:?}"));
+    }
+
+    #[test]
+    fn direct_downcasts() {
+        let mut obj = CHED::new(42, &Token::default());
+        assert!(obj.is::<i32>());
+        assert!(!obj.is::<u32>());
+        assert_eq!(Ok(&42), obj.downcast_ref::<i32>());
+        assert!(obj.downcast_ref::<i64>().is_err());
+
+        *obj.downcast_mut::<i32>().unwrap() = 13;
+        assert_eq!(Ok(13), obj.clone().downcast::<i32>());
+        let err = obj.downcast::<u8>().unwrap_err();
+        assert_eq!("u8", err.target_type_name);
+    }
+
+    #[test]
+    fn new_auto() {
+        let obj_1 = CHED::new_auto(42);
+        let obj_2 = CHED::new(42, &Token::default());
+        assert_eq!(obj_1, obj_2);
+        assert_ne!(obj_1, CHED::new_auto("42"));
+        assert_eq!("42", format!("{obj_1:?}"));
+    }
+
+    #[test]
+    fn type_identity() {
+        let obj = CHED::new_auto(42u16);
+        assert_eq!("u16", obj.type_name());
+        assert_eq!(TypeId::of::<u16>(), obj.type_id());
+    }
+
+    #[test]
+    fn display() {
+        let registry = Registry::new();
+        let vtable = VTable::of::<String>().with_display(crate::display::<String>);
+        registry.register::<String, _>(vtable);
+        let obj = CHED::new("foo".to_string(), &Token::in_registry(&registry));
+        assert_eq!("foo", obj.to_string());
+        assert_eq!("\"foo\"", format!("{obj:?}"));
+
+        let obj = CHED::new_auto("foo".to_string());
+        assert_eq!("\"foo\"", obj.to_string());
+    }
+
+    #[test]
+    fn send_ched() {
+        let obj = SendCHED::new(42, &Token::default());
+        let cloned = std::thread::spawn(move || obj.clone()).join().unwrap();
+        assert_eq!(SendCHED::new_auto(42), cloned);
+        assert_eq!("42", format!("{cloned:?}"));
+        assert_eq!(Ok(&42), cloned.downcast_ref::<i32>());
+
+        let obj = CHED::from(cloned);
+        assert_eq!(CHED::new_auto(42), obj);
+    }
+
+    #[test]
+    fn arc_ched() {
+        let obj_1 = ArcCHED::new(String::from("foo"), &Token::default());
+        let obj_2 = obj_1.clone();
+        assert!(Arc::ptr_eq(obj_1.inner(), obj_2.inner()));
+        assert_eq!(obj_1, obj_2);
+        assert_eq!(ArcCHED::new_auto(String::from("foo")), obj_1);
+        assert_ne!(ArcCHED::new_auto(String::from("bar")), obj_1);
+        assert_eq!("\"foo\"", format!("{obj_1:?}"));
+
+        let owned = obj_1.to_owned_ched();
+        assert_eq!(CHED::new_auto(String::from("foo")), owned);
+        assert_eq!(obj_1, ArcCHED::from(owned));
+    }
+
+    #[test]
+    fn arc_ched_copy_on_write() {
+        let mut obj_1 = ArcCHED::new_auto(42);
+        let obj_2 = obj_1.clone();
+        assert!(obj_1.is_shared());
+
+        *obj_1.downcast_mut::<i32>().unwrap() = 13;
+        assert!(!obj_1.is_shared());
+        assert!(!obj_2.is_shared());
+        assert_eq!(Ok(&13), obj_1.downcast_ref::<i32>());
+        assert_eq!(Ok(&42), obj_2.downcast_ref::<i32>());
+
+        let before = Arc::as_ptr(obj_1.inner());
+        *obj_1.downcast_mut::<i32>().unwrap() = 7;
+        assert!(std::ptr::addr_eq(before, Arc::as_ptr(obj_1.inner())));
+
+        let mut obj_3 = obj_2.clone();
+        assert!(obj_3.downcast_mut::<u8>().is_err());
+        assert!(obj_3.is_shared());
+    }
+
+    #[test]
+    fn inline_storage() {
+        let small = CHED::new_auto(42u64);
+        assert!(small.is_inline());
+        assert!(small.clone().is_inline());
+        assert_eq!(small, small.clone());
+        assert_eq!(Ok(42), small.clone().downcast::<u64>());
+        assert_eq!(Ok(42), small.into_inner().downcast::<u64>());
+
+        let unit = CHED::new_auto(());
+        assert!(unit.is_inline());
+        assert_eq!("()", format!("{unit:?}"));
+
+        let large = CHED::new_auto([0u8; INLINE_CAPACITY + 1]);
+        assert!(!large.is_inline());
+        assert!(!large.clone().is_inline());
+
+        let overaligned = CHED::new_auto(1u128);
+        assert_eq!(align_of::<u128>() <= align_of::<usize>(), overaligned.is_inline());
+    }
+
+    #[test]
+    fn inline_storage_drops_value() {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Counted(Rc<()>);
+
+        let counter = Rc::new(());
+        let mut obj = CHED::new_auto(Counted(counter.clone()));
+        assert!(obj.is_inline());
+        let cloned = obj.clone();
+        assert_eq!(3, Rc::strong_count(&counter));
+
+        obj.downcast_mut::<Counted>().unwrap().0 = Rc::new(());
+        assert_eq!(2, Rc::strong_count(&counter));
+        drop(cloned);
+        assert_eq!(1, Rc::strong_count(&counter));
+
+        let obj = CHED::new_auto(Counted(counter.clone()));
+        let boxed = obj.into_inner();
+        assert_eq!(2, Rc::strong_count(&counter));
+        drop(boxed);
+        assert_eq!(1, Rc::strong_count(&counter));
+    }
+
+    #[test]
+    fn mismatched_static_vtable_is_boxed() {
+        static VTABLE: VTable = VTable::of::<u8>();
+        static TOKEN: Token<u16, VTable> = Token::from_static(&VTABLE);
+
+        let obj = CHED::new(42u16, &TOKEN);
+        assert!(!obj.is_inline());
+        assert_eq!(Ok(&42u16), obj.downcast_ref());
+    }
+
+    #[test]
+    fn replace_and_take() {
+        let mut obj = CHED::new_auto(String::from("foo"));
+        assert_eq!(Ok(String::from("foo")), obj.replace(String::from("bar")));
+        assert_eq!(Ok(&String::from("bar")), obj.downcast_ref());
+        assert!(obj.replace(42).is_err());
+
+        let obj = obj.take_with_token::<i32>(&Token::default()).unwrap_err();
+        assert_eq!(Ok(String::from("bar")), obj.take_with_token(&Token::default()));
+
+        static VTABLE: VTable = VTable::of::<String>();
+        static TOKEN: Token<String, VTable> = Token::from_static(&VTABLE);
+        let obj = CHED::new(String::from("baz"), &TOKEN);
+        let obj = obj.take_with_token(&Token::<String, VTable>::default()).unwrap_err();
+        assert_eq!(Ok(String::from("baz")), obj.take_with_token(&TOKEN));
+    }
+
+    #[test]
+    fn map_ref_and_mut() {
+        let mut obj = CHED::new_auto(vec![1, 2]);
+        assert_eq!(Ok(2), obj.map_ref(Vec::<i32>::len));
+        assert_eq!(Ok(()), obj.map_mut(|vec: &mut Vec<i32>| vec.push(3)));
+        assert_eq!(Ok(&vec![1, 2, 3]), obj.downcast_ref());
+        assert!(obj.map_ref(|_: &String| ()).is_err());
+    }
+
+    #[test]
+    fn compare_with_value() {
+        let obj = CHED::new_auto(42);
+        assert!(obj.eq_value(&42));
+        assert!(!obj.eq_value(&43));
+        assert!(!obj.eq_value(&42u8));
+        assert!(obj == &42 as &dyn Every);
+        assert!(obj != &"42" as &dyn Every);
+    }
+
+    #[test]
+    fn borrowed_lookup() {
+        let mut map = HashMap::new();
+        map.insert(CHED::new_auto(42), "int");
+        map.insert(CHED::new_auto(String::from("foo")), "string");
+
+        let foo = String::from("foo");
+        assert_eq!(Some(&"int"), map.get(&ChedKey::new_auto(&42) as &dyn ChedLookup));
+        assert_eq!(Some(&"string"), map.get(&ChedKey::new_auto(&foo) as &dyn ChedLookup));
+        assert_eq!(None, map.get(&ChedKey::new_auto(&43) as &dyn ChedLookup));
+        assert_eq!(None, map.get(&ChedKey::new_auto(&42u8) as &dyn ChedLookup));
+    }
+
+    #[test]
+    fn hash_includes_type() {
+        fn hash_of(value: &impl Hash) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        assert_ne!(hash_of(&CHED::new_auto(42i32)), hash_of(&CHED::new_auto(42u32)));
+        assert_eq!(hash_of(&CHED::new_auto(42i32)), hash_of(&CHED::new_auto(42i32)));
+
+        static I32: VTable = VTable::of::<i32>().without_type_hash();
+        static U32: VTable = VTable::of::<u32>().without_type_hash();
+        let obj_i32 = CHED::new(42i32, &Token::from_static(&I32));
+        let obj_u32 = CHED::new(42u32, &Token::from_static(&U32));
+        assert_eq!(hash_of(&obj_i32), hash_of(&obj_u32));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serialize() {
+        let registry = Registry::new();
+        let vtable = VTable::of::<Vec<u8>>().with_serialize(crate::serialize::<Vec<u8>>);
+        registry.register::<Vec<u8>, _>(vtable);
+        let obj = CHED::new(vec![1u8, 2], &Token::in_registry(&registry));
+        assert_eq!(
+            r#"["alloc::vec::Vec<u8>",[1,2]]"#,
+            serde_json::to_string(&obj).unwrap()
+        );
+
+        let err = serde_json::to_string(&CHED::new_auto(42)).unwrap_err();
+        assert_eq!("no serialization thunk for i32", err.to_string());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserialize() {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+        struct Reading {
+            sensor: String,
+            value: i64,
+        }
+
+        let vtable = VTable::of::<Reading>().with_serialize(crate::serialize::<Reading>);
+        Registry::singleton().register::<Reading, _>(vtable);
+        crate::factory::register_deserialize::<Reading>();
+
+        let reading = Reading {
+            sensor: "t1".into(),
+            value: -4,
+        };
+        let obj = CHED::new_auto(reading.clone());
+        let json = serde_json::to_string(&obj).unwrap();
+        let deserialized = serde_json::from_str::<CHED>(&json).unwrap();
+        assert_eq!(obj, deserialized);
+        assert_eq!(Ok(&reading), deserialized.downcast_ref());
+
+        let err = serde_json::from_str::<CHED>(r#"["bool",true]"#).unwrap_err();
+        assert!(err.to_string().starts_with("no vtable specialised for bool"));
+    }
+
+    #[test]
+    fn recover_token() {
+        let registry = Registry::new();
+        let obj = CHED::new(42u8, &Token::in_registry(&registry));
+        let registered = Token::<u8, VTable>::in_registry(&registry);
+        assert!(std::ptr::eq(registered.vtable_ref(), obj.vtable()));
+
+        let token = obj.token::<u8>().unwrap();
+        assert!(std::ptr::eq(obj.vtable(), token.vtable_ref()));
+        assert_eq!(obj, CHED::new(42u8, &token));
+        assert!(obj.token::<i8>().is_err());
+    }
+
+    #[test]
+    fn ched_ref() {
+        let (x, y) = (String::from("foo"), String::from("foo"));
+        let ref_x = ChedRef::new_auto(&x);
+        assert_eq!(ref_x, ChedRef::new_auto(&y));
+        assert_ne!(ref_x, ChedRef::new_auto(&42));
+        assert_eq!("\"foo\"", format!("{ref_x:?}"));
+        assert_eq!(Ok(&x), ref_x.downcast_ref());
+
+        let obj = ref_x.to_ched();
+        assert_eq!(ref_x, obj);
+        assert_eq!(obj.as_ched_ref(), ref_x);
+
+        let mut hasher_1 = DefaultHasher::new();
+        obj.hash(&mut hasher_1);
+        let mut hasher_2 = DefaultHasher::new();
+        ref_x.hash(&mut hasher_2);
+        assert_eq!(hasher_1.finish(), hasher_2.finish());
+
+        let map = HashMap::from([(obj, 1)]);
+        assert_eq!(Some(&1), map.get(&ref_x as &dyn ChedLookup));
+    }
+
+    #[test]
+    fn pooled() {
+        type Large = [u64; 8];
+        let pool = Pool::new();
+        let token = Token::<Large, VTable>::default();
+
+        let obj = CHED::new_pooled([1; 8], &token, &pool);
+        assert!(obj.is_pooled());
+        let address = obj.inner() as *const dyn Every;
+        let cloned = obj.clone();
+        assert!(cloned.is_pooled());
+        assert_eq!(obj, cloned);
+
+        drop(obj);
+        assert_eq!(1, pool.idle());
+        let obj = CHED::new_pooled([2; 8], &token, &pool);
+        assert!(std::ptr::addr_eq(address, obj.inner()));
+        assert_eq!(0, pool.idle());
+
+        assert_eq!(Ok([2; 8]), obj.downcast::<Large>());
+        assert_eq!(Ok([1; 8]), cloned.into_inner().downcast::<Large>());
+        assert_eq!(2, pool.idle());
+
+        let small = CHED::new_pooled(42u8, &Token::default(), &pool);
+        assert!(small.is_inline());
+    }
+
+    #[test]
+    fn pooled_outlives_pool() {
+        let counter = Rc::new(());
+        let pool = Pool::with_limit(1);
+        let obj_1 = CHED::new_pooled(vec![counter.clone(); 4], &Token::default(), &pool);
+        let obj_2 = obj_1.clone();
+        let obj_3 = obj_1.clone();
+        drop(pool);
+
+        drop(obj_1);
+        drop(obj_2);
+        assert_eq!(5, Rc::strong_count(&counter));
+        drop(obj_3);
+        assert_eq!(1, Rc::strong_count(&counter));
+    }
+
+    #[test]
+    fn try_new() {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Warm(u8);
+
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Cold(u8);
+
+        let _ = Token::<Warm, VTable>::default();
+        assert_eq!(Ok(&Warm(1)), CHED::try_new(Warm(1)).unwrap().downcast_ref());
+
+        let err = CHED::try_new(Cold(2)).unwrap_err();
+        assert_eq!(Cold(2), err.0);
+        assert!(err.to_string().starts_with("no vtable registered for "));
+        assert!(Token::<Cold, VTable>::try_existing().is_none());
+    }
+
+    #[test]
+    fn interner() {
+        let interner = Interner::new();
+        let tok = Token::default();
+        let foo_1 = interner.intern(String::from("foo"), &tok);
+        let foo_2 = interner.intern(String::from("foo"), &tok);
+        let bar = interner.intern_ched(CHED::new_auto(String::from("bar")));
+        assert_eq!(foo_1, foo_2);
+        assert_ne!(foo_1, bar);
+        assert!(Arc::ptr_eq(foo_1.inner(), foo_2.inner()));
+        assert_eq!(foo_1, interner.intern_ched(CHED::new_auto(String::from("foo"))));
+        assert_eq!(2, interner.len());
+        assert_eq!("\"bar\"", format!("{bar:?}"));
+        assert_eq!(Ok(&String::from("bar")), bar.downcast_ref());
+
+        drop(bar);
+        interner.purge();
+        assert_eq!(1, interner.len());
+        drop((foo_1, foo_2));
+        interner.purge();
+        assert!(interner.is_empty());
+    }
+
+    #[test]
+    fn deep_size_of() {
+        let registry = Registry::new();
+        let vtable = VTable::of::<String>().with_deep_size(crate::deep_size::<String>);
+        registry.register::<String, _>(vtable);
+        let obj = CHED::new(String::with_capacity(100), &Token::in_registry(&registry));
+        let stored = if obj.is_inline() { 0 } else { size_of::<String>() };
+        assert_eq!(size_of::<CHED>() + stored + 100, obj.deep_size_of());
+
+        let obj = CHED::new_auto([0u8; INLINE_CAPACITY + 1]);
+        assert_eq!(size_of::<CHED>() + INLINE_CAPACITY + 1, obj.deep_size_of());
+    }
+
+    #[test]
+    fn erased_box_conversions() {
+        let any = CHED::new_auto(42).into_any();
+        assert_eq!(Some(&42), any.downcast_ref::<i32>());
+
+        let every = Box::<dyn Every>::from(CHED::new_auto(String::from("foo")));
+        let obj = CHED::from_every(every, &Token::<String, VTable>::default()).unwrap();
+        assert_eq!(CHED::new_auto(String::from("foo")), obj);
+
+        let every = Box::new(42u8) as Box<dyn Every>;
+        let registry = Registry::new();
+        let token = Token::<i8, VTable>::in_registry(&registry);
+        let err = CHED::from_every(every, &token).unwrap_err();
+        assert_eq!("u8", err.source_type_name);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn stable_hash() {
+        let registry = Registry::new();
+        type Key = (u32, String);
+        registry.register::<Key, _>(VTable::of::<Key>().with_serialize(crate::serialize::<Key>));
+        let tok = Token::in_registry(&registry);
+        let obj = CHED::new((7u32, String::from("foo")), &tok);
+        assert_eq!(obj.stable_hash(), CHED::new((7u32, String::from("foo")), &tok).stable_hash());
+        assert_ne!(obj.stable_hash(), CHED::new((8u32, String::from("foo")), &tok).stable_hash());
+        assert_eq!(Ok(0x786f_222b_9c36_e370), obj.stable_hash());
+
+        assert!(CHED::new_auto(42).stable_hash().is_err());
+    }
+
+    #[test]
+    fn thin_ched() {
+        assert_eq!(size_of::<usize>(), size_of::<ThinCHED>());
+
+        let mut obj = ThinCHED::new_auto(String::from("foo"));
+        let cloned = obj.clone();
+        assert_eq!(obj, cloned);
+        assert_eq!("\"foo\"", format!("{obj:?}"));
+        obj.downcast_mut::<String>().unwrap().push('!');
+        assert_ne!(obj, cloned);
+        assert_eq!(Ok(String::from("foo!")), obj.downcast());
+        assert_eq!(CHED::new_auto(String::from("foo")), CHED::from(cloned));
+
+        let unit = ThinCHED::new_auto(());
+        assert_eq!(unit, unit.clone());
+        let aligned = ThinCHED::new_auto(7u128);
+        assert_eq!(Ok(&7u128), aligned.downcast_ref());
+    }
+
+    #[test]
+    #[should_panic(expected = "vtable was not specialised for u16")]
+    fn thin_ched_mismatched_vtable() {
+        static VTABLE: VTable = VTable::of::<u8>();
+        static TOKEN: Token<u16, VTable> = Token::from_static(&VTABLE);
+        ThinCHED::new(42u16, &TOKEN);
+    }
+
+    #[test]
+    fn diff() {
+        use crate::diff::{DiffReport, DynDiff};
+
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        impl DynDiff for Point {
+            fn diff(&self, other: &Self, report: &mut DiffReport) {
+                report.compare("x", &self.x, &other.x);
+                report.compare("y", &self.y, &other.y);
+            }
+        }
+
+        // Debug fallback
+        let registry = Registry::new();
+        let tok = Token::<Point, VTable>::in_registry(&registry);
+        let lhs = CHED::new(Point { x: 1, y: 2 }, &tok);
+        assert!(lhs.diff(&lhs.clone()).is_empty());
+        let report = lhs.diff(&CHED::new(Point { x: 1, y: 3 }, &tok));
+        assert_eq!("line 3: y: 2, != y: 3,", report.to_string());
+
+        // via DynDiff
+        static DIFF: VTable = VTable::of::<Point>().with_diff(crate::diff::<Point>);
+        let tok = Token::<Point, VTable>::from_static(&DIFF);
+        let lhs = CHED::new(Point { x: 1, y: 2 }, &tok);
+        let report = lhs.diff(&CHED::new(Point { x: 0, y: 3 }, &tok));
+        assert_eq!("x: 1 != 0\ny: 2 != 3", report.to_string());
+
+        // differing types
+        let report = lhs.diff(&CHED::new(String::from("point"), &Token::default()));
+        assert_eq!(1, report.differences().len());
+        assert_eq!("type", report.differences()[0].path);
+        assert!(report.differences()[0].right.ends_with("String"));
+    }
+
+    #[test]
+    fn ptr_eq() {
+        let obj = CHED::new_auto(String::from("foo"));
+        let copy = obj.clone();
+        assert!(obj.ptr_eq(&obj));
+        assert!(!obj.ptr_eq(&copy));
+        assert_eq!(obj, copy);
+
+        let inline = CHED::new_auto(42u32);
+        assert!(inline.is_inline());
+        assert!(inline.ptr_eq(&inline));
+        assert!(!inline.ptr_eq(&inline.clone()));
+
+        assert!(obj.as_ched_ref().ptr_eq(&obj.as_ched_ref()));
+        assert!(!obj.as_ched_ref().ptr_eq(&copy.as_ched_ref()));
+
+        let arc = ArcCHED::from(obj);
+        assert!(arc.ptr_eq(&arc.clone()));
+        assert!(!arc.ptr_eq(&ArcCHED::from(copy)));
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn new_in() {
+        use crate::pool::Allocator;
+        use std::alloc::{GlobalAlloc, Layout, System};
+        use std::ptr::NonNull;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Default)]
+        struct Counting {
+            live: AtomicUsize,
+        }
+
+        unsafe impl Allocator for Counting {
+            fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+                self.live.fetch_add(1, Ordering::Relaxed);
+                NonNull::new(unsafe { System.alloc(layout) })
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.live.fetch_sub(1, Ordering::Relaxed);
+                unsafe { System.dealloc(ptr.as_ptr(), layout) }
+            }
+        }
+
+        type Large = [u64; 8];
+        let allocator = Arc::new(Counting::default());
+        let token = Token::<Large, VTable>::default();
+
+        let obj = CHED::new_in([1; 8], &token, &allocator);
+        assert!(obj.is_pooled());
+        let cloned = obj.clone();
+        assert_eq!(2, allocator.live.load(Ordering::Relaxed));
+        assert_eq!(obj, cloned);
+
+        drop(obj);
+        assert_eq!(1, allocator.live.load(Ordering::Relaxed));
+        assert_eq!(Ok([1; 8]), cloned.downcast::<Large>());
+        assert_eq!(0, allocator.live.load(Ordering::Relaxed));
+
+        let small = CHED::new_in(42u8, &Token::default(), &allocator);
+        assert!(small.is_inline());
+        assert_eq!(0, allocator.live.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn drop_hook() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Tracked(u64);
+
+        static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+        fn on_drop(value: &mut dyn Every) {
+            let value = value.downcast_mut::<Tracked>().unwrap();
+            DROPPED.fetch_add(value.0 as usize, Ordering::Relaxed);
+        }
+
+        let registry = Registry::new();
+        let tok = registry.register::<Tracked, _>(VTable::of::<Tracked>().with_drop_hook(on_drop));
+        let obj = CHED::new(Tracked(1), &tok);
+        assert!(obj.is_inline());
+        drop(obj.clone());
+        assert_eq!(1, DROPPED.load(Ordering::Relaxed));
+        drop(CHED::from_parts(Box::new(Tracked(10)), tok.vtable_ref()));
+        assert_eq!(11, DROPPED.load(Ordering::Relaxed));
+        drop(ThinCHED::new(Tracked(100), &tok));
+        assert_eq!(111, DROPPED.load(Ordering::Relaxed));
+
+        // moving the value out does not invoke the hook
+        assert_eq!(Ok(Tracked(1)), obj.downcast());
+        assert_eq!(111, DROPPED.load(Ordering::Relaxed));
+
+        // the default vtable carries no hook
+        drop(CHED::new(Tracked(1000), &Token::in_registry(&Registry::new())));
+        assert_eq!(111, DROPPED.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn id_ched() {
+        assert!(size_of::<IdCHED>() < size_of::<CHED>());
+
+        let mut obj = IdCHED::new(String::from("foo"));
+        assert_eq!(Registry::singleton().id::<String, VTable>(), obj.id());
+        assert_eq!(obj, obj.clone());
+        assert_eq!(obj.id(), obj.clone().id());
+        assert_eq!("\"foo\"", format!("{obj:?}"));
+        obj.downcast_mut::<String>().unwrap().push('!');
+        assert_ne!(obj, IdCHED::new(String::from("foo")));
+        assert_eq!(CHED::new_auto(String::from("foo!")), CHED::from(obj.clone()));
+        assert_eq!(Ok(String::from("foo!")), obj.downcast());
+
+        // overriding the entry keeps its identifier, which then resolves to the override
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Pin(u32);
+
+        let obj = IdCHED::new(Pin(1234));
+        let id = obj.id();
+        Registry::singleton().register::<Pin, _>(VTable::of::<Pin>().redacted());
+        assert_eq!(id, Registry::singleton().id::<Pin, VTable>());
+        assert!(!format!("{obj:?}").contains("1234"));
+    }
+
+    #[test]
+    #[should_panic(expected = "vtable was not constructed for u32")]
+    fn from_static_checked_mismatch() {
+        static I32: VTable = VTable::of::<i32>();
+        Token::<u32, VTable>::from_static_checked(&I32);
+    }
+
+    #[test]
+    fn drop_hook_siblings() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Tracked(u64);
+
+        static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+        fn on_drop(value: &mut dyn Every) {
+            let value = value.downcast_mut::<Tracked>().unwrap();
+            DROPPED.fetch_add(value.0 as usize, Ordering::Relaxed);
+        }
+
+        let registry = Registry::new();
+        let tok = registry.register::<Tracked, _>(VTable::of::<Tracked>().with_drop_hook(on_drop));
+
+        drop(SendCHED::new(Tracked(1), &tok));
+        assert_eq!(1, DROPPED.load(Ordering::Relaxed));
+
+        // converting between dynamic objects carries the hook without invoking it
+        let obj = CHED::from(SendCHED::new(Tracked(10), &tok));
+        assert_eq!(1, DROPPED.load(Ordering::Relaxed));
+        drop(obj);
+        assert_eq!(11, DROPPED.load(Ordering::Relaxed));
+
+        // shared objects invoke the hook only when the last of them is dropped
+        let arc = ArcCHED::from(CHED::new(Tracked(100), &tok));
+        drop(arc.clone());
+        assert_eq!(11, DROPPED.load(Ordering::Relaxed));
+        drop(arc);
+        assert_eq!(111, DROPPED.load(Ordering::Relaxed));
+
+        let frozen = SendCHED::new(Tracked(1000), &tok).freeze();
+        drop(frozen.clone());
+        assert_eq!(111, DROPPED.load(Ordering::Relaxed));
+        drop(frozen);
+        assert_eq!(1111, DROPPED.load(Ordering::Relaxed));
+
+        let interner = Interner::new();
+        let interned = interner.intern(Tracked(10000), &tok);
+        drop(interner);
+        assert_eq!(1111, DROPPED.load(Ordering::Relaxed));
+        drop(interned);
+        assert_eq!(11111, DROPPED.load(Ordering::Relaxed));
+
+        // surrendering the value does not invoke the hook
+        drop(SendCHED::new(Tracked(100000), &tok).into_inner());
+        drop(ArcCHED::from(CHED::new(Tracked(100000), &tok)).into_inner());
+        assert_eq!(11111, DROPPED.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn try_clone() {
+        use crate::ched::{CloneError, TryClone};
+
+        #[derive(Debug, PartialEq, Eq, Hash)]
+        struct Handle {
+            id: u32,
+            closed: bool,
+        }
+
+        impl TryClone for Handle {
+            fn try_clone(&self) -> Result<Self, CloneError> {
+                if self.closed {
+                    return Err(CloneError::new("handle closed"));
+                }
+                Ok(Self { id: self.id, closed: false })
+            }
+        }
+
+        static HANDLE: VTable = VTable::of_try_clone::<Handle>();
+        let tok = Token::<Handle, VTable>::from_static_checked(&HANDLE);
+        let open = CHED::new(Handle { id: 7, closed: false }, &tok);
+        assert_eq!(open, open.try_clone().unwrap());
+        assert_eq!(open, open.clone());
+
+        let closed = CHED::new(Handle { id: 7, closed: true }, &tok);
+        let err = closed.try_clone().unwrap_err();
+        assert_eq!("clone failed: handle closed", err.to_string());
+        let clone = std::panic::AssertUnwindSafe(|| closed.clone());
+        let panicked = std::panic::catch_unwind(clone).unwrap_err();
+        let message = panicked.downcast_ref::<String>().unwrap();
+        assert!(message.ends_with("Handle: clone failed: handle closed"));
+
+        // infallibly cloneable types take the usual path
+        let obj = CHED::new_auto(42u32);
+        assert!(obj.try_clone().unwrap().is_inline());
+    }
+
+    #[test]
+    fn new_boxed() {
+        let boxed = Box::new(vec![0u8; 1024]);
+        let address = &*boxed as *const Vec<u8>;
+        let obj = CHED::new_boxed(boxed, &Token::default());
+        assert!(std::ptr::addr_eq(address, obj.inner()));
+        assert_eq!(Ok(&vec![0u8; 1024]), obj.downcast_ref());
+
+        let obj = CHED::new_boxed(Box::new(42u8), &Token::default());
+        assert!(!obj.is_inline());
+        assert_eq!(CHED::new_auto(42u8), obj);
+    }
+
+    #[test]
+    fn deref() {
+        fn describe(value: &dyn Every) -> &'static str {
+            value.type_name()
+        }
+
+        let mut obj = CHED::new_auto(String::from("foo"));
+        assert_eq!(describe(&*obj), describe(obj.as_ref()));
+        assert!(describe(&*obj).ends_with("String"));
+        assert!(describe(&obj).ends_with("CHED"));
+        assert_eq!(Ok(3), (*obj).downcast_ref::<String>().map(String::len));
+
+        obj.as_mut().downcast_mut::<String>().unwrap().push_str("bar");
+        assert_eq!(CHED::new_auto(String::from("foobar")), obj);
+    }
+
+    #[test]
+    fn typed() {
+        let mut obj = Typed::new(vec![1, 2], &Token::default());
+        obj.push(3);
+        assert_eq!(3, obj.len());
+        assert_eq!("[1, 2, 3]", format!("{obj:?}"));
+        let ched = obj.clone().into_ched();
+        assert_eq!(CHED::new_auto(vec![1, 2, 3]), ched);
+        assert_eq!(vec![1, 2, 3], obj.into_value());
+
+        let mut inline = Typed::<u32>::from_ched(CHED::new_auto(42u32)).unwrap();
+        assert!(inline.as_ched().is_inline());
+        *inline += 1;
+        assert_eq!(CHED::new_auto(43u32), CHED::from(inline));
+
+        let pooled = CHED::new_pooled([7u64; 8], &Token::default(), &Pool::new());
+        assert_eq!([7; 8], *Typed::<[u64; 8]>::from_ched(pooled).unwrap());
+
+        let mismatched = Typed::<u16>::from_ched(CHED::new_auto(42u32)).unwrap_err();
+        assert_eq!(CHED::new_auto(42u32), mismatched);
+    }
+
+    #[test]
+    #[allow(clippy::mutable_key_type)]
+    fn hashed_ched() {
+        use std::cell::Cell;
+
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct Costly(Rc<Cell<usize>>, u32);
+
+        impl Hash for Costly {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.0.set(self.0.get() + 1);
+                self.1.hash(state);
+            }
+        }
+
+        let hashes = Rc::new(Cell::new(0));
+        let tok = Token::<Costly, VTable>::in_registry(&Registry::new());
+        let mut obj = HashedCHED::new(CHED::new(Costly(hashes.clone(), 1), &tok));
+        assert!(!obj.is_memoized());
+        obj.hash_code();
+        let mut map_1 = HashMap::new();
+        let mut map_2 = HashMap::new();
+        map_1.insert(obj.clone(), ());
+        map_2.insert(obj.clone(), ());
+        assert!(map_1.contains_key(&obj));
+        assert!(obj.is_memoized());
+        assert_eq!(1, hashes.get());
+
+        obj.downcast_mut::<Costly>().unwrap().1 = 2;
+        assert!(!obj.is_memoized());
+        assert!(!map_1.contains_key(&obj));
+        assert_eq!(2, hashes.get());
+
+        let other = HashedCHED::from(CHED::new(Costly(hashes.clone(), 2), &tok));
+        assert_eq!(obj, other);
+        assert_eq!(Ok(2), obj.downcast_ref::<Costly>().map(|costly| costly.1));
+        assert_eq!(CHED::from(other), obj.into_ched());
+    }
+
+    #[test]
+    fn hash_one() {
+        use std::hash::{BuildHasherDefault, RandomState};
+
+        let obj = CHED::new_auto(String::from("foo"));
+        let build_hasher = BuildHasherDefault::<DefaultHasher>::default();
+        let mut hasher = DefaultHasher::new();
+        obj.hash(&mut hasher);
+        assert_eq!(hasher.finish(), obj.hash_one(&build_hasher));
+
+        let state = RandomState::new();
+        assert_eq!(obj.hash_one(&state), obj.clone().hash_one(&state));
+        assert_eq!(obj.hash_code(), obj.clone().hash_code());
+        assert_ne!(obj.hash_code(), CHED::new_auto(String::from("bar")).hash_code());
+        assert_eq!(obj.hash_code(), HashedCHED::new(obj).hash_code());
+    }
+
+    #[test]
+    fn redacted() {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Password(&'static str);
+
+        let registry = Registry::new();
+        let tok = VTable::register_redacted::<Password>(&registry);
+        let secret = CHED::new(Password("hunter2"), &tok);
+        let type_name = std::any::type_name::<Password>();
+        assert_eq!(format!("<redacted {type_name}>"), format!("{secret:?}"));
+        assert_eq!(format!("<redacted {type_name}>"), secret.to_string());
+        assert_eq!(secret, secret.clone());
+        assert_ne!(secret, CHED::new(Password("letmein"), &tok));
+        let report = secret.diff(&CHED::new(Password("x"), &tok));
+        assert!(!format!("{report:?}").contains("hunter2"));
+        assert_eq!(Ok(&Password("hunter2")), secret.downcast_ref());
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize_on_drop() {
+        use std::cell::RefCell;
+        use zeroize::Zeroize;
+
+        // shares its secret with the test, so that the wiping may be observed after the drop
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct Key(Rc<RefCell<Vec<u8>>>);
+
+        impl Hash for Key {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.0.borrow().hash(state);
+            }
+        }
+
+        impl Zeroize for Key {
+            fn zeroize(&mut self) {
+                self.0.borrow_mut().zeroize();
+            }
+        }
+
+        static KEY: VTable = VTable::of_zeroizing::<Key>().redacted();
+        let secret = Rc::new(RefCell::new(vec![0xAA; 32]));
+        let obj = CHED::new(Key(secret.clone()), &Token::from_static(&KEY));
+        assert_eq!(32, secret.borrow().len());
+        drop(obj);
+        assert!(secret.borrow().is_empty());
+        assert_eq!(32, secret.borrow().capacity());
+
+        // shared values are wiped once the last of their owners is dropped
+        secret.borrow_mut().extend([0xAA; 32]);
+        let shared = ArcCHED::from(CHED::new(Key(secret.clone()), &Token::from_static(&KEY)));
+        drop(shared.clone());
+        assert_eq!(32, secret.borrow().len());
+        drop(shared);
+        assert!(secret.borrow().is_empty());
+
+        let registry = Registry::new();
+        let tok = VTable::register_zeroizing::<String>(&registry);
+        let password = CHED::new(String::from("hunter2"), &tok);
+        assert!(!format!("{password:?}").contains("hunter2"));
+        assert!(tok.vtable_ref().on_drop.is_some());
+    }
+
+    #[test]
+    fn tracked_ched() {
+        let mut obj = TrackedCHED::new(CHED::new_auto(vec![1, 2]));
+        assert_eq!(0, obj.version());
+        assert!(!obj.is_dirty());
+        assert_eq!(Ok(2), obj.downcast_ref::<Vec<i32>>().map(Vec::len));
+        assert!(!obj.is_dirty());
+
+        obj.downcast_mut::<Vec<i32>>().unwrap().push(3);
+        assert_eq!(Ok(()), obj.map_mut(|vec: &mut Vec<i32>| vec.push(4)));
+        assert_eq!(2, obj.version());
+        assert!(obj.is_dirty());
+        obj.mark_clean();
+        assert!(!obj.is_dirty());
+
+        // failed downcasts grant no access
+        assert!(obj.downcast_mut::<String>().is_err());
+        assert!(!obj.is_dirty());
+        obj.inner_mut();
+        assert_eq!(3, obj.version());
+        assert!(obj.is_dirty());
+        assert_eq!(CHED::new_auto(vec![1, 2, 3, 4]), obj.into_ched());
+    }
+
+    #[test]
+    fn frozen_ched() {
+        fn assert_sync<T: Send + Sync>(_: &T) {}
+
+        let frozen = CHED::new_auto(String::from("foo")).freeze();
+        let shared = frozen.clone();
+        assert!(frozen.ptr_eq(&shared));
+        assert_eq!(frozen, shared);
+        assert_eq!(Ok(&String::from("foo")), frozen.downcast_ref());
+        assert_eq!("\"foo\"", format!("{frozen:?}"));
+
+        let mut thawed = frozen.thaw();
+        thawed.downcast_mut::<String>().unwrap().push('!');
+        assert_eq!(Ok(&String::from("foo")), shared.downcast_ref());
+        assert_eq!(CHED::new_auto(String::from("foo!")), thawed);
+
+        let frozen: FrozenCHED<dyn Every + Send + Sync> = SendCHED::new_auto(42u32).freeze();
+        assert_sync(&frozen);
+        let reader = frozen.clone();
+        let value = std::thread::spawn(move || *reader.downcast_ref::<u32>().unwrap());
+        assert_eq!(42, value.join().unwrap());
+        assert!(frozen.is::<u32>());
+    }
+
+    #[test]
+    fn redacted_after_new_auto() {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Password(&'static str);
+
+        assert_eq!("Password(\"hunter2\")", format!("{:?}", CHED::new_auto(Password("hunter2"))));
+        VTable::register_redacted::<Password>(Registry::singleton());
+        let obj = CHED::new_auto(Password("hunter2"));
+        let type_name = std::any::type_name::<Password>();
+        assert_eq!(format!("<redacted {type_name}>"), format!("{obj:?}"));
+    }
+
+    #[cfg(feature = "shared")]
+    #[test]
+    fn shared_token() {
+        use crate::vtable::shared_token;
+        use std::any;
+        use std::fmt::Debug;
+        use std::ptr;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use vtable_shared::{get, get_or_insert, Lookup, SharedVTable};
+
+        struct Rendered<'a>(&'a SharedVTable, *const ());
+
+        impl Debug for Rendered<'_> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                unsafe { (self.0.debug)(self.1, f) }
+            }
+        }
+
+        // requested here first, the vtable is exported, continuing to reflect overrides
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Local(u32);
+
+        let token = shared_token::<Local, VTable>();
+        assert_eq!(token, Token::default());
+        let exported = get(any::type_name::<Local>(), any::type_name::<VTable>()).unwrap();
+        let value = Local(42);
+        let rendered = Rendered(exported, ptr::from_ref(&value).cast());
+        assert_eq!("Local(42)", format!("{rendered:?}"));
+        VTable::register_redacted::<Local>(Registry::singleton());
+        let type_name = any::type_name::<Local>();
+        assert_eq!(format!("<redacted {type_name}>"), format!("{rendered:?}"));
+
+        // published by another version of the crate, the vtable is adopted
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Foreign(u32);
+
+        static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+        fn foreign<'a>(this: *const ()) -> &'a Foreign {
+            unsafe { &*this.cast::<Foreign>() }
+        }
+
+        let foreign_vtable = || SharedVTable {
+            type_id: TypeId::of::<Foreign>(),
+            debug: |this, f| write!(f, "foreign {}", foreign(this).0),
+            display: |this, f| write!(f, "{}", foreign(this).0),
+            eq: |this, other| foreign(this).0 % 2 == foreign(other).0 % 2,
+            hash: |this, state| state.write_u32(foreign(this).0 % 2),
+            hash_type: true,
+            drop_hook: |_| {
+                DROPPED.fetch_add(1, Ordering::Relaxed);
+            },
+        };
+        let names = (any::type_name::<Foreign>(), any::type_name::<VTable>());
+        let lookup = get_or_insert(names.0, names.1, TypeId::of::<Foreign>(), foreign_vtable);
+        assert!(matches!(lookup, Lookup::Published));
+
+        let token = shared_token::<Foreign, VTable>();
+        assert_eq!(token, shared_token());
+        assert_eq!(token, Token::default());
+        let obj = CHED::new(Foreign(1), &token);
+        assert_eq!("foreign 1", format!("{obj:?}"));
+        assert_eq!("1", obj.to_string());
+        let other = CHED::new(Foreign(3), &token);
+        assert_eq!(obj, other);
+        assert_eq!(obj.hash_code(), other.hash_code());
+        assert_ne!(obj, CHED::new(Foreign(2), &token));
+        assert_ne!(obj, CHED::new_auto(1u32));
+        let dropped = DROPPED.load(Ordering::Relaxed);
+        drop(obj);
+        assert_eq!(dropped + 1, DROPPED.load(Ordering::Relaxed));
+
+        // published for a different type under the same names, the vtable is specialised locally
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Impostor(u32);
+
+        let names = (any::type_name::<Impostor>(), any::type_name::<VTable>());
+        let lookup = get_or_insert(names.0, names.1, TypeId::of::<Foreign>(), foreign_vtable);
+        assert!(matches!(lookup, Lookup::Published));
+        let obj = CHED::new(Impostor(1), &shared_token());
+        assert_eq!("Impostor(1)", format!("{obj:?}"));
     }
 }
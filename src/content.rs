@@ -0,0 +1,844 @@
+//! A self-describing intermediate form of serialized values, letting them pass through the
+//! non-generic thunks of a vtable, which cannot be parametrised by a [serde::Serializer] or a
+//! [serde::Deserializer].
+//!
+//! Values are captured in the shape of a self-describing format such as JSON: structs become maps
+//! keyed by field name, tuples become sequences and enum variants are externally tagged.
+//! Conversely, [Content] is itself a [serde::Deserializer], from which the original value may be
+//! reconstructed; it can only be deserialized from self-describing formats, however.
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Content {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Content>),
+    Unit,
+    Seq(Vec<Content>),
+    Map(Vec<(Content, Content)>),
+}
+
+/// An error raised while capturing a value as [Content], or reconstructing one from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentError(pub String);
+
+impl Display for ContentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for ContentError {}
+
+impl ser::Error for ContentError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl de::Error for ContentError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl Content {
+    /// Captures a serializable value.
+    pub fn of<T: Serialize + ?Sized>(value: &T) -> Result<Self, ContentError> {
+        value.serialize(Capture)
+    }
+
+    /// A canonical byte encoding of the content, independent of the platform and of the order in
+    /// which map entries were serialized.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.encode(&mut bytes);
+        bytes
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        fn encode_len(len: usize, out: &mut Vec<u8>) {
+            out.extend_from_slice(&(len as u64).to_le_bytes());
+        }
+
+        match self {
+            Self::Bool(value) => out.extend_from_slice(&[0, *value as u8]),
+            Self::I64(value) => {
+                out.push(1);
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+            Self::U64(value) => {
+                out.push(2);
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+            Self::F64(value) => {
+                out.push(3);
+                let value = if value.is_nan() { f64::NAN } else { *value };
+                out.extend_from_slice(&value.to_bits().to_le_bytes());
+            }
+            Self::Char(value) => {
+                out.push(4);
+                out.extend_from_slice(&u32::from(*value).to_le_bytes());
+            }
+            Self::String(value) => {
+                out.push(5);
+                encode_len(value.len(), out);
+                out.extend_from_slice(value.as_bytes());
+            }
+            Self::Bytes(value) => {
+                out.push(6);
+                encode_len(value.len(), out);
+                out.extend_from_slice(value);
+            }
+            Self::None => out.push(7),
+            Self::Some(value) => {
+                out.push(8);
+                value.encode(out);
+            }
+            Self::Unit => out.push(9),
+            Self::Seq(elements) => {
+                out.push(10);
+                encode_len(elements.len(), out);
+                for element in elements {
+                    element.encode(out);
+                }
+            }
+            Self::Map(entries) => {
+                out.push(11);
+                encode_len(entries.len(), out);
+                let mut encoded = entries
+                    .iter()
+                    .map(|(key, value)| {
+                        let mut entry = key.canonical_bytes();
+                        value.encode(&mut entry);
+                        entry
+                    })
+                    .collect::<Vec<_>>();
+                encoded.sort_unstable();
+                for entry in encoded {
+                    out.extend_from_slice(&entry);
+                }
+            }
+        }
+    }
+
+    fn tagged(variant: &'static str, content: Content) -> Self {
+        Self::Map(vec![(Self::String(variant.into()), content)])
+    }
+}
+
+impl Serialize for Content {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use ser::{SerializeMap, SerializeSeq};
+
+        match self {
+            Self::Bool(value) => serializer.serialize_bool(*value),
+            Self::I64(value) => serializer.serialize_i64(*value),
+            Self::U64(value) => serializer.serialize_u64(*value),
+            Self::F64(value) => serializer.serialize_f64(*value),
+            Self::Char(value) => serializer.serialize_char(*value),
+            Self::String(value) => serializer.serialize_str(value),
+            Self::Bytes(value) => serializer.serialize_bytes(value),
+            Self::None => serializer.serialize_none(),
+            Self::Some(value) => serializer.serialize_some(value),
+            Self::Unit => serializer.serialize_unit(),
+            Self::Seq(elements) => {
+                let mut seq = serializer.serialize_seq(Some(elements.len()))?;
+                for element in elements {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            Self::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Content {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ContentVisitor)
+    }
+}
+
+struct ContentVisitor;
+
+impl<'de> Visitor<'de> for ContentVisitor {
+    type Value = Content;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("any value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Content, E> {
+        Ok(Content::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Content, E> {
+        Ok(Content::I64(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Content, E> {
+        Ok(Content::U64(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Content, E> {
+        Ok(Content::F64(v))
+    }
+
+    fn visit_char<E>(self, v: char) -> Result<Content, E> {
+        Ok(Content::Char(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Content, E> {
+        Ok(Content::String(v.into()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Content, E> {
+        Ok(Content::String(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Content, E> {
+        Ok(Content::Bytes(v.into()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Content, E> {
+        Ok(Content::Bytes(v))
+    }
+
+    fn visit_none<E>(self) -> Result<Content, E> {
+        Ok(Content::None)
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Content, D::Error> {
+        let content = de::Deserialize::deserialize(deserializer)?;
+        Ok(Content::Some(Box::new(content)))
+    }
+
+    fn visit_unit<E>(self) -> Result<Content, E> {
+        Ok(Content::Unit)
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Content, D::Error> {
+        de::Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Content, A::Error> {
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(Content::Seq(elements))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Content, A::Error> {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or_default());
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Content::Map(entries))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Content {
+    type Error = ContentError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ContentError> {
+        match self {
+            Self::Bool(v) => visitor.visit_bool(v),
+            Self::I64(v) => visitor.visit_i64(v),
+            Self::U64(v) => visitor.visit_u64(v),
+            Self::F64(v) => visitor.visit_f64(v),
+            Self::Char(v) => visitor.visit_char(v),
+            Self::String(v) => visitor.visit_string(v),
+            Self::Bytes(v) => visitor.visit_byte_buf(v),
+            Self::None => visitor.visit_none(),
+            Self::Some(v) => visitor.visit_some(*v),
+            Self::Unit => visitor.visit_unit(),
+            Self::Seq(elements) => {
+                let mut seq = SeqDeserializer::new(elements.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Self::Map(entries) => {
+                let mut map = MapDeserializer::new(entries.into_iter());
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ContentError> {
+        match self {
+            Self::None | Self::Unit => visitor.visit_none(),
+            Self::Some(v) => visitor.visit_some(*v),
+            other => visitor.visit_some(other),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ContentError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ContentError> {
+        match self {
+            Self::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Self::Map(mut entries) if entries.len() == 1 => {
+                let (variant, value) = entries.pop().unwrap();
+                visitor.visit_enum(Tagged { variant, value })
+            }
+            _ => Err(ContentError("expected a variant name or a single-entry map".into())),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, ContentError> for Content {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// An externally tagged enum variant.
+struct Tagged {
+    variant: Content,
+    value: Content,
+}
+
+impl<'de> de::EnumAccess<'de> for Tagged {
+    type Error = ContentError;
+    type Variant = Content;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Content), ContentError> {
+        Ok((seed.deserialize(self.variant)?, self.value))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Content {
+    type Error = ContentError;
+
+    fn unit_variant(self) -> Result<(), ContentError> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, ContentError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ContentError> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ContentError> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
+
+/// A [ser::Serializer] yielding [Content].
+struct Capture;
+
+/// Accumulates the elements of a compound value, optionally tagged with an enum variant.
+struct CaptureSeq {
+    variant: Option<&'static str>,
+    elements: Vec<Content>,
+}
+
+impl CaptureSeq {
+    fn new(variant: Option<&'static str>, len: usize) -> Self {
+        Self {
+            variant,
+            elements: Vec::with_capacity(len),
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ContentError> {
+        self.elements.push(Content::of(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Content {
+        let seq = Content::Seq(self.elements);
+        match self.variant {
+            Some(variant) => Content::tagged(variant, seq),
+            None => seq,
+        }
+    }
+}
+
+/// Accumulates the entries of a map or struct, optionally tagged with an enum variant.
+struct CaptureMap {
+    variant: Option<&'static str>,
+    entries: Vec<(Content, Content)>,
+    key: Option<Content>,
+}
+
+impl CaptureMap {
+    fn new(variant: Option<&'static str>, len: usize) -> Self {
+        Self {
+            variant,
+            entries: Vec::with_capacity(len),
+            key: None,
+        }
+    }
+
+    fn field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ContentError> {
+        self.entries.push((Content::String(key.into()), Content::of(value)?));
+        Ok(())
+    }
+
+    fn finish(self) -> Content {
+        let map = Content::Map(self.entries);
+        match self.variant {
+            Some(variant) => Content::tagged(variant, map),
+            None => map,
+        }
+    }
+}
+
+impl ser::Serializer for Capture {
+    type Ok = Content;
+    type Error = ContentError;
+    type SerializeSeq = CaptureSeq;
+    type SerializeTuple = CaptureSeq;
+    type SerializeTupleStruct = CaptureSeq;
+    type SerializeTupleVariant = CaptureSeq;
+    type SerializeMap = CaptureMap;
+    type SerializeStruct = CaptureMap;
+    type SerializeStructVariant = CaptureMap;
+
+    fn serialize_bool(self, v: bool) -> Result<Content, ContentError> {
+        Ok(Content::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Content, ContentError> {
+        Ok(Content::I64(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Content, ContentError> {
+        Ok(Content::I64(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Content, ContentError> {
+        Ok(Content::I64(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Content, ContentError> {
+        Ok(Content::I64(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Content, ContentError> {
+        i64::try_from(v)
+            .map(Content::I64)
+            .map_err(|_| ContentError(format!("{v} is out of range of i64")))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Content, ContentError> {
+        Ok(Content::U64(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Content, ContentError> {
+        Ok(Content::U64(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Content, ContentError> {
+        Ok(Content::U64(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Content, ContentError> {
+        Ok(Content::U64(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Content, ContentError> {
+        u64::try_from(v)
+            .map(Content::U64)
+            .map_err(|_| ContentError(format!("{v} is out of range of u64")))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Content, ContentError> {
+        Ok(Content::F64(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Content, ContentError> {
+        Ok(Content::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Content, ContentError> {
+        Ok(Content::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Content, ContentError> {
+        Ok(Content::String(v.into()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Content, ContentError> {
+        Ok(Content::Bytes(v.into()))
+    }
+
+    fn serialize_none(self) -> Result<Content, ContentError> {
+        Ok(Content::None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Content, ContentError> {
+        Ok(Content::Some(Box::new(Content::of(value)?)))
+    }
+
+    fn serialize_unit(self) -> Result<Content, ContentError> {
+        Ok(Content::Unit)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Content, ContentError> {
+        Ok(Content::Unit)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Content, ContentError> {
+        Ok(Content::String(variant.into()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Content, ContentError> {
+        Content::of(value)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Content, ContentError> {
+        Ok(Content::tagged(variant, Content::of(value)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<CaptureSeq, ContentError> {
+        Ok(CaptureSeq::new(None, len.unwrap_or_default()))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<CaptureSeq, ContentError> {
+        Ok(CaptureSeq::new(None, len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<CaptureSeq, ContentError> {
+        Ok(CaptureSeq::new(None, len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<CaptureSeq, ContentError> {
+        Ok(CaptureSeq::new(Some(variant), len))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<CaptureMap, ContentError> {
+        Ok(CaptureMap::new(None, len.unwrap_or_default()))
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<CaptureMap, ContentError> {
+        Ok(CaptureMap::new(None, len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<CaptureMap, ContentError> {
+        Ok(CaptureMap::new(Some(variant), len))
+    }
+}
+
+impl ser::SerializeSeq for CaptureSeq {
+    type Ok = Content;
+    type Error = ContentError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ContentError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content, ContentError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for CaptureSeq {
+    type Ok = Content;
+    type Error = ContentError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ContentError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content, ContentError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for CaptureSeq {
+    type Ok = Content;
+    type Error = ContentError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ContentError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content, ContentError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleVariant for CaptureSeq {
+    type Ok = Content;
+    type Error = ContentError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ContentError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content, ContentError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeMap for CaptureMap {
+    type Ok = Content;
+    type Error = ContentError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ContentError> {
+        self.key = Some(Content::of(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ContentError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ContentError("map value serialized before its key".into()))?;
+        self.entries.push((key, Content::of(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Content, ContentError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStruct for CaptureMap {
+    type Ok = Content;
+    type Error = ContentError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ContentError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<Content, ContentError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStructVariant for CaptureMap {
+    type Ok = Content;
+    type Error = ContentError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ContentError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<Content, ContentError> {
+        Ok(self.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::content::Content;
+    use serde::Deserialize;
+    use std::collections::BTreeMap;
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    struct Point {
+        x: i32,
+        y: Option<f64>,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    enum Shape {
+        Empty,
+        Circle(u8),
+        Line(Point, Point),
+        Rect { width: u16, height: u16 },
+    }
+
+    fn string(value: &str) -> Content {
+        Content::String(value.into())
+    }
+
+    #[test]
+    fn capture_struct() {
+        let point = Point { x: -1, y: Some(2.5) };
+        assert_eq!(
+            Content::Map(vec![
+                (string("x"), Content::I64(-1)),
+                (string("y"), Content::Some(Box::new(Content::F64(2.5)))),
+            ]),
+            Content::of(&point).unwrap()
+        );
+    }
+
+    #[test]
+    fn capture_enum() {
+        assert_eq!(string("Empty"), Content::of(&Shape::Empty).unwrap());
+        assert_eq!(
+            Content::Map(vec![(string("Circle"), Content::U64(3))]),
+            Content::of(&Shape::Circle(3)).unwrap()
+        );
+
+        let line = Shape::Line(Point { x: 0, y: None }, Point { x: 1, y: None });
+        let Content::Map(entries) = Content::of(&line).unwrap() else {
+            panic!("expected a map");
+        };
+        assert_eq!(string("Line"), entries[0].0);
+        assert!(matches!(&entries[0].1, Content::Seq(points) if points.len() == 2));
+
+        let rect = Shape::Rect { width: 2, height: 3 };
+        assert_eq!(
+            Content::Map(vec![(
+                string("Rect"),
+                Content::Map(vec![
+                    (string("width"), Content::U64(2)),
+                    (string("height"), Content::U64(3)),
+                ])
+            )]),
+            Content::of(&rect).unwrap()
+        );
+    }
+
+    #[test]
+    fn canonical_bytes() {
+        let ordered = Content::of(&BTreeMap::from([("a", 1), ("b", 2)])).unwrap();
+        let Content::Map(mut entries) = ordered.clone() else {
+            panic!("expected a map");
+        };
+        entries.reverse();
+        assert_eq!(ordered.canonical_bytes(), Content::Map(entries).canonical_bytes());
+
+        assert_ne!(Content::I64(1).canonical_bytes(), Content::U64(1).canonical_bytes());
+        assert_ne!(
+            Content::Seq(vec![string("ab")]).canonical_bytes(),
+            Content::Seq(vec![string("a"), string("b")]).canonical_bytes()
+        );
+        assert_eq!([9], *Content::Unit.canonical_bytes());
+    }
+
+    #[test]
+    fn capture_out_of_range() {
+        assert!(Content::of(&u128::MAX).is_err());
+        assert_eq!(Content::U64(7), Content::of(&7u128).unwrap());
+    }
+
+    #[test]
+    fn round_trip_through_json() {
+        let map = BTreeMap::from([("a", vec![1, 2]), ("b", vec![])]);
+        let content = Content::of(&map).unwrap();
+        assert_eq!(serde_json::to_string(&map).unwrap(), serde_json::to_string(&content).unwrap());
+    }
+
+    #[test]
+    fn reconstruct() {
+        let shapes = vec![
+            Shape::Empty,
+            Shape::Circle(3),
+            Shape::Line(Point { x: 0, y: None }, Point { x: 1, y: Some(0.5) }),
+            Shape::Rect { width: 2, height: 3 },
+        ];
+        let content = Content::of(&shapes).unwrap();
+        assert_eq!(shapes, Vec::<Shape>::deserialize(content).unwrap());
+
+        let json = serde_json::to_string(&shapes).unwrap();
+        let content = serde_json::from_str::<Content>(&json).unwrap();
+        assert_eq!(shapes, Vec::<Shape>::deserialize(content).unwrap());
+    }
+
+    #[test]
+    fn reconstruct_mismatch() {
+        let content = Content::of(&"foo").unwrap();
+        assert!(Point::deserialize(content.clone()).is_err());
+        assert!(u8::deserialize(content).is_err());
+    }
+}
@@ -0,0 +1,117 @@
+//! Structured differences between dynamic values, explaining why they are unequal.
+//!
+//! Types implementing [DynDiff] report differences field by field. For other types, differences
+//! are found by comparing the lines of their pretty-printed [Debug] renderings.
+
+use std::fmt::{Debug, Display, Formatter};
+
+/// Reports how two values of the same type differ.
+pub trait DynDiff {
+    fn diff(&self, other: &Self, report: &mut DiffReport);
+}
+
+/// One point of difference between two values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// Where the values differ, e.g. a field name, or empty if they differ as a whole.
+    pub path: String,
+    pub left: String,
+    pub right: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffReport {
+    differences: Vec<Difference>,
+}
+
+impl DiffReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn differences(&self) -> &[Difference] {
+        &self.differences
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// Records a difference at the given path.
+    pub fn push(&mut self, path: impl Into<String>, left: &dyn Debug, right: &dyn Debug) {
+        self.differences.push(Difference {
+            path: path.into(),
+            left: format!("{left:?}"),
+            right: format!("{right:?}"),
+        });
+    }
+
+    /// Records a difference at the given path if the values are unequal.
+    pub fn compare<T: Debug + PartialEq + ?Sized>(&mut self, path: &str, left: &T, right: &T) {
+        if left != right {
+            self.push(path, &left, &right);
+        }
+    }
+
+    /// Compares the lines of two pretty-printed [Debug] renderings, identifying differences by
+    /// line number.
+    pub(crate) fn from_debug(left: &str, right: &str) -> Self {
+        let (mut left, mut right) = (left.lines(), right.lines());
+        let mut report = Self::new();
+        for line in 1.. {
+            match (left.next(), right.next()) {
+                (None, None) => break,
+                (lhs, rhs) if lhs != rhs => report.differences.push(Difference {
+                    path: format!("line {line}"),
+                    left: lhs.unwrap_or_default().trim().into(),
+                    right: rhs.unwrap_or_default().trim().into(),
+                }),
+                _ => {}
+            }
+        }
+        report
+    }
+}
+
+impl Display for DiffReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (index, difference) in self.differences.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            if !difference.path.is_empty() {
+                write!(f, "{}: ", difference.path)?;
+            }
+            write!(f, "{} != {}", difference.left, difference.right)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diff::DiffReport;
+
+    #[test]
+    fn from_debug() {
+        let report = DiffReport::from_debug(
+            "A {\n    x: 1,\n    y: 2,\n}",
+            "A {\n    x: 1,\n    y: 3,\n}",
+        );
+        assert_eq!("line 3: y: 2, != y: 3,", report.to_string());
+
+        let report = DiffReport::from_debug("[\n    1,\n]", "[\n    1,\n    2,\n]");
+        let paths = report.differences().iter().map(|d| &d.path).collect::<Vec<_>>();
+        assert_eq!(vec!["line 3", "line 4"], paths);
+
+        assert!(DiffReport::from_debug("42", "42").is_empty());
+    }
+
+    #[test]
+    fn compare() {
+        let mut report = DiffReport::new();
+        report.compare("a", &1, &1);
+        report.compare("b", "x", "y");
+        assert_eq!("b: \"x\" != \"y\"", report.to_string());
+    }
+}
@@ -5,19 +5,89 @@
 //! as upcasting coercion from `&dyn Every` to `&dyn Any` was not stable at the time.
 //! See [feature(trait_upcasting)](https://github.com/rust-lang/rust/issues/65991).
 
+use crate::vtable;
+use crate::vtable::{SendMarker, SyncMarker};
 use std::any;
 use std::any::{Any, TypeId};
+use std::borrow::Cow;
 use std::error::Error;
-use std::fmt::{Display, Formatter};
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
 
 pub trait Every: Any {
     fn type_name(&self) -> &'static str;
+
+    fn type_info(&self) -> TypeInfo;
+
+    /// The type name with module paths stripped. See [short_type_name].
+    fn type_name_short(&self) -> String;
 }
 
 impl<T: 'static + ?Sized> Every for T {
     fn type_name(&self) -> &'static str {
         any::type_name::<Self>()
     }
+
+    fn type_info(&self) -> TypeInfo {
+        TypeInfo {
+            type_id: TypeId::of::<Self>(),
+            type_name: any::type_name::<Self>(),
+            size: mem::size_of_val(self),
+            align: mem::align_of_val(self),
+        }
+    }
+
+    fn type_name_short(&self) -> String {
+        short_type_name(any::type_name::<Self>())
+    }
+}
+
+/// Strips module paths from a fully-qualified type name, keeping generic arguments intact.
+/// For example, `my_crate::module::Foo<alloc::string::String>` becomes `Foo<String>`.
+pub fn short_type_name(type_name: &str) -> String {
+    let mut short = String::with_capacity(type_name.len());
+    let mut segment_start = 0;
+    let mut chars = type_name.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            let after_segment = short[segment_start..]
+                .chars()
+                .next()
+                .is_some_and(is_ident_char);
+            if after_segment {
+                short.truncate(segment_start);
+            } else {
+                short.push_str("::");
+            }
+            segment_start = short.len();
+        } else {
+            if !is_ident_char(ch) {
+                segment_start = short.len() + ch.len_utf8();
+            }
+            short.push(ch);
+        }
+    }
+    short
+}
+
+#[inline]
+fn is_ident_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// Metadata describing the concrete type behind a `dyn Every`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TypeInfo {
+    pub type_id: TypeId,
+    pub type_name: &'static str,
+    pub size: usize,
+    pub align: usize,
 }
 
 pub trait AsEvery: Every {
@@ -59,6 +129,16 @@ impl dyn Every {
         TypeId::of::<T>() == self.type_id()
     }
 
+    #[inline]
+    pub fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    #[inline]
+    pub fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
     #[inline]
     pub fn downcast_ref<T: Every>(&self) -> Result<&T, DowncastError> {
         self.__downcast_ref::<T>()
@@ -67,33 +147,97 @@ impl dyn Every {
 
     #[inline]
     pub fn downcast_mut<T: Every>(&mut self) -> Result<&mut T, DowncastError> {
-        let self_ptr: *const dyn Every = self;
-        self.__downcast_mut::<T>().ok_or_else(|| {
-            // SAFETY: when `Option::None` is returned, no mutable references to self are held
-            let self_alias = unsafe { &*self_ptr };
-            cannot_downcast::<T>(self_alias)
-        })
-    }
-
-    #[inline]
-    fn __downcast_ref<T: Every>(&self) -> Option<&T> {
         if self.is::<T>() {
             // SAFETY: just checked whether we are pointing to the correct type, and we can rely on
             // that check for memory safety because we have implemented Any for all types; no other
             // impls can exist as they would conflict with our impl.
-            unsafe { Some(self.__downcast_ref_unchecked()) }
+            unsafe { Ok(self.__downcast_mut_unchecked()) }
         } else {
-            None
+            Err(cannot_downcast::<T>(self))
         }
     }
 
+    /// Returns a reference to the inner value as type `T`, or `default` if the type does not match.
     #[inline]
-    fn __downcast_mut<T: Every>(&mut self) -> Option<&mut T> {
+    pub fn downcast_ref_or<'a, T: Every>(&'a self, default: &'a T) -> &'a T {
+        self.__downcast_ref::<T>().unwrap_or(default)
+    }
+
+    /// Returns a reference to the inner value as type `T`, or computes a fallback if the type does
+    /// not match.
+    #[inline]
+    pub fn downcast_ref_or_else<'a, T: Every>(&'a self, f: impl FnOnce() -> &'a T) -> &'a T {
+        self.__downcast_ref::<T>().unwrap_or_else(f)
+    }
+
+    /// Returns a thin pointer to the value, retaining the provenance of `self`.
+    #[inline]
+    pub fn as_ptr(&self) -> *const () {
+        (self as *const dyn Every).cast()
+    }
+
+    /// Returns a thin mutable pointer to the value, retaining the provenance of `self`.
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut () {
+        (self as *mut dyn Every).cast()
+    }
+
+    /// Returns a reference to an unsized value, such as a `str` or a `[u8]`. As `dyn Every` cannot
+    /// hold an unsized type directly, the value must be held behind a `Box<U>`, a `&'static U`,
+    /// an `Rc<U>` or an `Arc<U>`; the fat-pointer metadata is preserved.
+    #[inline]
+    pub fn downcast_unsized_ref<U: ?Sized + 'static>(&self) -> Result<&U, DowncastError> {
+        if let Some(value) = self.__downcast_ref::<Box<U>>() {
+            Ok(value)
+        } else if let Some(value) = self.__downcast_ref::<&'static U>() {
+            Ok(value)
+        } else if let Some(value) = self.__downcast_ref::<Rc<U>>() {
+            Ok(value)
+        } else if let Some(value) = self.__downcast_ref::<Arc<U>>() {
+            Ok(value)
+        } else {
+            Err(cannot_downcast::<U>(self))
+        }
+    }
+
+    /// Returns a mutable reference to an unsized value held behind a `Box<U>`.
+    #[inline]
+    pub fn downcast_unsized_mut<U: ?Sized + 'static>(&mut self) -> Result<&mut U, DowncastError> {
+        if self.is::<Box<U>>() {
+            // SAFETY: just checked whether we are pointing to the correct type
+            Ok(unsafe { self.__downcast_mut_unchecked::<Box<U>>() })
+        } else {
+            Err(cannot_downcast::<U>(self))
+        }
+    }
+
+    /// Returns a reference to the inner value as type `T`, without checking the type.
+    ///
+    /// # Safety
+    /// The contained value must be of type `T`. Calling this method with the incorrect type is
+    /// *undefined behavior*.
+    #[inline]
+    pub unsafe fn downcast_ref_unchecked<T: Every>(&self) -> &T {
+        unsafe { self.__downcast_ref_unchecked() }
+    }
+
+    /// Returns a mutable reference to the inner value as type `T`, without checking the type.
+    ///
+    /// # Safety
+    /// The contained value must be of type `T`. Calling this method with the incorrect type is
+    /// *undefined behavior*.
+    #[inline]
+    pub unsafe fn downcast_mut_unchecked<T: Every>(&mut self) -> &mut T {
+        unsafe { self.__downcast_mut_unchecked() }
+    }
+
+    #[inline]
+    fn __downcast_ref<T: Every>(&self) -> Option<&T> {
         if self.is::<T>() {
             // SAFETY: just checked whether we are pointing to the correct type, and we can rely on
             // that check for memory safety because we have implemented Any for all types; no other
             // impls can exist as they would conflict with our impl.
-            unsafe { Some(self.__downcast_mut_unchecked()) }
+            unsafe { Some(self.__downcast_ref_unchecked()) }
         } else {
             None
         }
@@ -103,14 +247,14 @@ impl dyn Every {
     unsafe fn __downcast_ref_unchecked<T: Every>(&self) -> &T {
         debug_assert!(self.is::<T>());
         // SAFETY: caller guarantees that T is the correct type
-        unsafe { &*(self as *const dyn Every as *const T) }
+        unsafe { &*self.as_ptr().cast::<T>() }
     }
 
     #[inline]
     unsafe fn __downcast_mut_unchecked<T: Every>(&mut self) -> &mut T {
         debug_assert!(self.is::<T>());
         // SAFETY: caller guarantees that T is the correct type
-        unsafe { &mut *(self as *mut dyn Every as *mut T) }
+        unsafe { &mut *self.as_mut_ptr().cast::<T>() }
     }
 }
 
@@ -120,6 +264,36 @@ impl dyn Every + Send {
         <dyn Every>::is::<T>(self)
     }
 
+    #[inline]
+    pub fn downcast_ref_or<'a, T: Every>(&'a self, default: &'a T) -> &'a T {
+        <dyn Every>::downcast_ref_or::<T>(self, default)
+    }
+
+    #[inline]
+    pub fn downcast_ref_or_else<'a, T: Every>(&'a self, f: impl FnOnce() -> &'a T) -> &'a T {
+        <dyn Every>::downcast_ref_or_else::<T>(self, f)
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *const () {
+        <dyn Every>::as_ptr(self)
+    }
+
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut () {
+        <dyn Every>::as_mut_ptr(self)
+    }
+
+    #[inline]
+    pub fn as_any(&self) -> &dyn Any {
+        <dyn Every>::as_any(self)
+    }
+
+    #[inline]
+    pub fn as_any_mut(&mut self) -> &mut dyn Any {
+        <dyn Every>::as_any_mut(self)
+    }
+
     #[inline]
     pub fn downcast_ref<T: Every>(&self) -> Result<&T, DowncastError> {
         <dyn Every>::downcast_ref::<T>(self)
@@ -129,6 +303,34 @@ impl dyn Every + Send {
     pub fn downcast_mut<T: Every>(&mut self) -> Result<&mut T, DowncastError> {
         <dyn Every>::downcast_mut::<T>(self)
     }
+
+    /// Forwards to the method defined on the type `dyn Every`.
+    ///
+    /// # Safety
+    /// Same as [`<dyn Every>::downcast_ref_unchecked`].
+    #[inline]
+    pub unsafe fn downcast_ref_unchecked<T: Every>(&self) -> &T {
+        unsafe { <dyn Every>::downcast_ref_unchecked::<T>(self) }
+    }
+
+    /// Forwards to the method defined on the type `dyn Every`.
+    ///
+    /// # Safety
+    /// Same as [`<dyn Every>::downcast_mut_unchecked`].
+    #[inline]
+    pub unsafe fn downcast_mut_unchecked<T: Every>(&mut self) -> &mut T {
+        unsafe { <dyn Every>::downcast_mut_unchecked::<T>(self) }
+    }
+
+    #[inline]
+    pub fn downcast_unsized_ref<U: ?Sized + 'static>(&self) -> Result<&U, DowncastError> {
+        <dyn Every>::downcast_unsized_ref::<U>(self)
+    }
+
+    #[inline]
+    pub fn downcast_unsized_mut<U: ?Sized + 'static>(&mut self) -> Result<&mut U, DowncastError> {
+        <dyn Every>::downcast_unsized_mut::<U>(self)
+    }
 }
 
 impl dyn Every + Send + Sync {
@@ -137,6 +339,36 @@ impl dyn Every + Send + Sync {
         <dyn Every>::is::<T>(self)
     }
 
+    #[inline]
+    pub fn downcast_ref_or<'a, T: Every>(&'a self, default: &'a T) -> &'a T {
+        <dyn Every>::downcast_ref_or::<T>(self, default)
+    }
+
+    #[inline]
+    pub fn downcast_ref_or_else<'a, T: Every>(&'a self, f: impl FnOnce() -> &'a T) -> &'a T {
+        <dyn Every>::downcast_ref_or_else::<T>(self, f)
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *const () {
+        <dyn Every>::as_ptr(self)
+    }
+
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut () {
+        <dyn Every>::as_mut_ptr(self)
+    }
+
+    #[inline]
+    pub fn as_any(&self) -> &dyn Any {
+        <dyn Every>::as_any(self)
+    }
+
+    #[inline]
+    pub fn as_any_mut(&mut self) -> &mut dyn Any {
+        <dyn Every>::as_any_mut(self)
+    }
+
     #[inline]
     pub fn downcast_ref<T: Every>(&self) -> Result<&T, DowncastError> {
         <dyn Every>::downcast_ref::<T>(self)
@@ -146,20 +378,126 @@ impl dyn Every + Send + Sync {
     pub fn downcast_mut<T: Every>(&mut self) -> Result<&mut T, DowncastError> {
         <dyn Every>::downcast_mut::<T>(self)
     }
+
+    /// Forwards to the method defined on the type `dyn Every`.
+    ///
+    /// # Safety
+    /// Same as [`<dyn Every>::downcast_ref_unchecked`].
+    #[inline]
+    pub unsafe fn downcast_ref_unchecked<T: Every>(&self) -> &T {
+        unsafe { <dyn Every>::downcast_ref_unchecked::<T>(self) }
+    }
+
+    /// Forwards to the method defined on the type `dyn Every`.
+    ///
+    /// # Safety
+    /// Same as [`<dyn Every>::downcast_mut_unchecked`].
+    #[inline]
+    pub unsafe fn downcast_mut_unchecked<T: Every>(&mut self) -> &mut T {
+        unsafe { <dyn Every>::downcast_mut_unchecked::<T>(self) }
+    }
+
+    #[inline]
+    pub fn downcast_unsized_ref<U: ?Sized + 'static>(&self) -> Result<&U, DowncastError> {
+        <dyn Every>::downcast_unsized_ref::<U>(self)
+    }
+
+    #[inline]
+    pub fn downcast_unsized_mut<U: ?Sized + 'static>(&mut self) -> Result<&mut U, DowncastError> {
+        <dyn Every>::downcast_unsized_mut::<U>(self)
+    }
 }
 
-fn cannot_downcast<T: Every>(source: &dyn Every) -> DowncastError {
+pub(crate) fn cannot_downcast<T: Every + ?Sized>(source: &dyn Every) -> DowncastError {
     DowncastError {
         source_type_id: source.type_id(),
-        source_type_name: source.type_name(),
+        source_type_name: Cow::Borrowed(source.type_name()),
         target_type_id: TypeId::of::<T>(),
-        target_type_name: any::type_name::<T>(),
+        target_type_name: Cow::Borrowed(any::type_name::<T>()),
+        source_debug: vtable::debug_snapshot(source),
+        suggestion: vtable::suggest_type_name(source.type_name(), any::type_name::<T>())
+            .map(Box::from),
+    }
+}
+
+impl<T: 'static> From<Box<T>> for Box<dyn Every> {
+    #[inline]
+    fn from(value: Box<T>) -> Self {
+        value
     }
 }
 
+/// Converts a `Box<dyn Any>` into a `Box<dyn Every>`, provided it holds a `T`. Otherwise, the
+/// original box is returned. See [from_any] for a conversion that need not name `T`.
+#[inline]
+pub fn try_from_any<T: 'static>(value: Box<dyn Any>) -> Result<Box<dyn Every>, Box<dyn Any>> {
+    value.downcast::<T>().map(|value| value as Box<dyn Every>)
+}
+
+/// Converts a `Box<dyn Any>` into a `Box<dyn Every>` without naming the concrete type, provided
+/// a vtable for it has been specialised (or registered) in the singleton
+/// [Registry](vtable::Registry), e.g. by constructing a [CHED](crate::ched::CHED) of that type.
+/// Otherwise, the original box is returned.
+#[inline]
+pub fn from_any(value: Box<dyn Any>) -> Result<Box<dyn Every>, Box<dyn Any>> {
+    vtable::from_any_registered(value)
+}
+
 /// Extension methods for `Box<dyn Every>`.
 pub trait BoxDowncast {
     fn downcast<T: 'static>(self) -> Result<T, DowncastError>;
+
+    /// Consumes the box, returning the inner value as type `T`, without checking the type.
+    ///
+    /// # Safety
+    /// The contained value must be of type `T`. Calling this method with the incorrect type is
+    /// *undefined behavior*.
+    ///
+    /// The default implementation defers to [BoxDowncast::downcast], eliding the error path.
+    #[inline]
+    unsafe fn downcast_unchecked<T: 'static>(self) -> T
+    where
+        Self: Sized,
+    {
+        unsafe { self.downcast::<T>().unwrap_unchecked() }
+    }
+
+    /// Attempts to consume the box as type `T`. Unlike [BoxDowncast::downcast], the original box is
+    /// handed back alongside the error on failure, so that the caller may retry with another type.
+    fn try_downcast<T: 'static>(self) -> Result<T, (DowncastError, Self)>
+    where
+        Self: Sized;
+
+    /// Consumes the box as type `T`, or returns `default` if the type does not match.
+    #[inline]
+    fn downcast_or<T: 'static>(self, default: T) -> T
+    where
+        Self: Sized,
+    {
+        self.downcast_or_else(|| default)
+    }
+
+    /// Consumes the box as type `T`, or computes a fallback if the type does not match.
+    #[inline]
+    fn downcast_or_else<T: 'static>(self, f: impl FnOnce() -> T) -> T
+    where
+        Self: Sized,
+    {
+        match self.try_downcast::<T>() {
+            Ok(value) => value,
+            Err(_) => f(),
+        }
+    }
+
+    /// Consumes the box, returning the unsized value it holds as a `Box<U>`. See
+    /// [`<dyn Every>::downcast_unsized_ref`].
+    #[inline]
+    fn downcast_unsized<U: ?Sized + 'static>(self) -> Result<Box<U>, DowncastError>
+    where
+        Self: Sized,
+    {
+        self.downcast::<Box<U>>()
+    }
 }
 
 impl BoxDowncast for Box<dyn Every> {
@@ -169,6 +507,30 @@ impl BoxDowncast for Box<dyn Every> {
             .map(|this| *this)
             .map_err(|this| cannot_downcast::<T>(&*this))
     }
+
+    #[inline]
+    unsafe fn downcast_unchecked<T: 'static>(self) -> T {
+        unsafe { *__downcast_unchecked::<T>(self) }
+    }
+
+    /// Checks the type without building a [DowncastError] for a mismatch.
+    #[inline]
+    fn downcast_or_else<T: 'static>(self, f: impl FnOnce() -> T) -> T {
+        if self.is::<T>() {
+            unsafe { self.downcast_unchecked() }
+        } else {
+            f()
+        }
+    }
+
+    #[inline]
+    fn try_downcast<T: 'static>(self) -> Result<T, (DowncastError, Self)> {
+        if self.is::<T>() {
+            Ok(unsafe { self.downcast_unchecked() })
+        } else {
+            Err((cannot_downcast::<T>(&*self), self))
+        }
+    }
 }
 
 impl BoxDowncast for Box<dyn Every + Send> {
@@ -176,6 +538,25 @@ impl BoxDowncast for Box<dyn Every + Send> {
     fn downcast<T: 'static>(self) -> Result<T, DowncastError> {
         <Box<dyn Every>>::downcast(self)
     }
+
+    #[inline]
+    unsafe fn downcast_unchecked<T: 'static>(self) -> T {
+        unsafe { <Box<dyn Every>>::downcast_unchecked(self) }
+    }
+
+    #[inline]
+    fn downcast_or_else<T: 'static>(self, f: impl FnOnce() -> T) -> T {
+        <Box<dyn Every>>::downcast_or_else(self, f)
+    }
+
+    #[inline]
+    fn try_downcast<T: 'static>(self) -> Result<T, (DowncastError, Self)> {
+        if self.is::<T>() {
+            Ok(unsafe { self.downcast_unchecked() })
+        } else {
+            Err((cannot_downcast::<T>(&*self), self))
+        }
+    }
 }
 
 impl BoxDowncast for Box<dyn Every + Send + Sync> {
@@ -183,6 +564,25 @@ impl BoxDowncast for Box<dyn Every + Send + Sync> {
     fn downcast<T: 'static>(self) -> Result<T, DowncastError> {
         <Box<dyn Every>>::downcast(self)
     }
+
+    #[inline]
+    unsafe fn downcast_unchecked<T: 'static>(self) -> T {
+        unsafe { <Box<dyn Every>>::downcast_unchecked(self) }
+    }
+
+    #[inline]
+    fn downcast_or_else<T: 'static>(self, f: impl FnOnce() -> T) -> T {
+        <Box<dyn Every>>::downcast_or_else(self, f)
+    }
+
+    #[inline]
+    fn try_downcast<T: 'static>(self) -> Result<T, (DowncastError, Self)> {
+        if self.is::<T>() {
+            Ok(unsafe { self.downcast_unchecked() })
+        } else {
+            Err((cannot_downcast::<T>(&*self), self))
+        }
+    }
 }
 
 #[inline]
@@ -203,100 +603,572 @@ unsafe fn __downcast_unchecked<T: Every>(s: Box<dyn Every>) -> Box<T> {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct DowncastError {
-    pub source_type_id: TypeId,
-    pub source_type_name: &'static str,
-    pub target_type_id: TypeId,
-    pub target_type_name: &'static str,
+/// Downcasts every element of a type-homogeneous slice, failing on the first element that is not a `T`.
+pub fn downcast_slice<T: 'static>(slice: &[Box<dyn Every>]) -> Result<Vec<&T>, DowncastError> {
+    slice.iter().map(|value| value.downcast_ref::<T>()).collect()
 }
 
-impl Display for DowncastError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "cannot downcast {} into {}",
-            self.source_type_name, self.target_type_name
-        )
+/// Mutably downcasts every element of a type-homogeneous slice, failing on the first element that
+/// is not a `T`.
+pub fn downcast_slice_mut<T: 'static>(
+    slice: &mut [Box<dyn Every>],
+) -> Result<Vec<&mut T>, DowncastError> {
+    if let Some(value) = slice.iter().find(|value| !value.is::<T>()) {
+        return Err(cannot_downcast::<T>(&**value));
     }
+    Ok(slice
+        .iter_mut()
+        // SAFETY: just checked that every element is a `T`
+        .map(|value| unsafe { value.downcast_mut_unchecked::<T>() })
+        .collect())
 }
 
-impl Error for DowncastError {}
+/// Wraps a pointer to an erased value, implementing [Hash] and [Eq] by the address of the value
+/// rather than by its contents. The underlying type need not implement either trait.
+///
+/// Note: distinct values of a zero-sized type may share an address, and will compare equal.
+#[derive(Clone, Copy)]
+pub struct ByAddress<P>(pub P);
 
-pub fn panic<R>(error: impl Error) -> R {
-    panic!("{error}")
+impl<P: Deref<Target: Every>> ByAddress<P> {
+    #[inline]
+    pub fn addr(&self) -> *const () {
+        (&*self.0 as *const P::Target).cast()
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::every::{panic, BoxDowncast, DowncastError, Every};
-    use std::any::TypeId;
-    use std::panic::AssertUnwindSafe;
-    use std::{any, panic};
-
-    #[test]
-    fn is() {
-        let val = Box::new(42i32) as Box<dyn Every>;
-        assert!(val.is::<i32>());
-        assert!(!val.is::<&str>());
+impl<P: Deref<Target: Every>> PartialEq for ByAddress<P> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.addr() == other.addr()
     }
+}
 
-    #[test]
-    fn downcast_ref_ok() {
-        let val = Box::new(42i32) as Box<dyn Every>;
-        assert_eq!(Ok(&42i32), val.downcast_ref());
-    }
+impl<P: Deref<Target: Every>> Eq for ByAddress<P> {}
 
-    #[test]
-    fn downcast_ref_error() {
-        let val = Box::new(42i32) as Box<dyn Every>;
-        assert_eq!(
-            Err(DowncastError {
-                source_type_id: TypeId::of::<i32>(),
-                source_type_name: any::type_name::<i32>(),
-                target_type_id: TypeId::of::<&str>(),
-                target_type_name: any::type_name::<&str>(),
-            }),
-            val.downcast_ref::<&str>()
-        );
+impl<P: Deref<Target: Every>> Debug for ByAddress<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ByAddress").field(&self.addr()).finish()
     }
+}
 
-    #[test]
-    fn downcast_mut_ok() {
-        let mut val = Box::new(42i32) as Box<dyn Every>;
-        *val.downcast_mut().unwrap() = 13;
-        assert_eq!(Ok(&13i32), val.downcast_ref());
+impl<P: Deref<Target: Every>> Hash for ByAddress<P> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.addr().hash(state);
     }
+}
 
-    #[test]
-    fn downcast_mut_error() {
-        let mut val = Box::new(42i32) as Box<dyn Every>;
-        assert_eq!(
-            Err(DowncastError {
-                source_type_id: TypeId::of::<i32>(),
-                source_type_name: any::type_name::<i32>(),
-                target_type_id: TypeId::of::<&str>(),
-                target_type_name: any::type_name::<&str>(),
-            }),
-            val.downcast_mut::<&str>()
-        );
+impl<P> Deref for ByAddress<P> {
+    type Target = P;
+
+    #[inline]
+    fn deref(&self) -> &P {
+        &self.0
     }
+}
 
-    #[test]
-    fn downcast_ok() {
-        let val = Box::new(42i32) as Box<dyn Every>;
-        assert_eq!(Ok(42i32), val.downcast());
+/// An iterator item that may be downcast, yielding `Output<T>` on success.
+pub trait DowncastItem {
+    type Output<T: 'static>;
+
+    fn downcast_item<T: 'static>(self) -> Option<Self::Output<T>>;
+}
+
+impl<'a> DowncastItem for &'a dyn Every {
+    type Output<T: 'static> = &'a T;
+
+    #[inline]
+    fn downcast_item<T: 'static>(self) -> Option<&'a T> {
+        // SAFETY: the type was just checked
+        self.is::<T>().then(|| unsafe { self.downcast_ref_unchecked() })
     }
+}
 
-    #[test]
+impl<'a> DowncastItem for &'a mut dyn Every {
+    type Output<T: 'static> = &'a mut T;
+
+    #[inline]
+    fn downcast_item<T: 'static>(self) -> Option<&'a mut T> {
+        // SAFETY: the type was just checked
+        self.is::<T>().then(|| unsafe { self.downcast_mut_unchecked() })
+    }
+}
+
+impl<'a> DowncastItem for &'a Box<dyn Every> {
+    type Output<T: 'static> = &'a T;
+
+    #[inline]
+    fn downcast_item<T: 'static>(self) -> Option<&'a T> {
+        // SAFETY: the type was just checked
+        self.is::<T>().then(|| unsafe { self.downcast_ref_unchecked() })
+    }
+}
+
+impl<'a> DowncastItem for &'a mut Box<dyn Every> {
+    type Output<T: 'static> = &'a mut T;
+
+    #[inline]
+    fn downcast_item<T: 'static>(self) -> Option<&'a mut T> {
+        // SAFETY: the type was just checked
+        self.is::<T>().then(|| unsafe { self.downcast_mut_unchecked() })
+    }
+}
+
+impl DowncastItem for Box<dyn Every> {
+    type Output<T: 'static> = T;
+
+    #[inline]
+    fn downcast_item<T: 'static>(self) -> Option<T> {
+        // SAFETY: the type was just checked
+        self.is::<T>().then(|| unsafe { self.downcast_unchecked() })
+    }
+}
+
+/// Extension methods for iterators over erased values.
+pub trait DowncastFilter: Iterator<Item: DowncastItem> + Sized {
+    /// Yields only the items of type `T`, already downcast.
+    #[inline]
+    fn downcast_filter<T: 'static>(
+        self,
+    ) -> impl Iterator<Item = <Self::Item as DowncastItem>::Output<T>> {
+        self.filter_map(DowncastItem::downcast_item::<T>)
+    }
+}
+
+impl<I: Iterator<Item: DowncastItem>> DowncastFilter for I {}
+
+/// Converts a `Box<dyn Every>` into a thread-safe erased box, provided the auto traits of the
+/// concrete type were recorded by specialising [SendMarker] or [SyncMarker] for it. Otherwise, the
+/// original box is returned.
+pub trait BoxTryIntoSend: Sized {
+    fn try_into_send(self) -> Result<Box<dyn Every + Send>, Self>;
+
+    fn try_into_sync(self) -> Result<Box<dyn Every + Send + Sync>, Self>;
+}
+
+impl BoxTryIntoSend for Box<dyn Every> {
+    #[inline]
+    fn try_into_send(self) -> Result<Box<dyn Every + Send>, Self> {
+        let type_id = (*self).type_id();
+        if vtable::is_specialised::<SendMarker>(type_id)
+            || vtable::is_specialised::<SyncMarker>(type_id)
+        {
+            // SAFETY: the concrete type is known to be Send; auto traits do not alter the layout of
+            // the box or its vtable
+            Ok(unsafe { mem::transmute::<Box<dyn Every>, Box<dyn Every + Send>>(self) })
+        } else {
+            Err(self)
+        }
+    }
+
+    #[inline]
+    fn try_into_sync(self) -> Result<Box<dyn Every + Send + Sync>, Self> {
+        if vtable::is_specialised::<SyncMarker>((*self).type_id()) {
+            // SAFETY: the concrete type is known to be Send and Sync; auto traits do not alter the
+            // layout of the box or its vtable
+            Ok(unsafe { mem::transmute::<Box<dyn Every>, Box<dyn Every + Send + Sync>>(self) })
+        } else {
+            Err(self)
+        }
+    }
+}
+
+/// Extension methods for `Pin<Box<dyn Every>>`, preserving the pinning guarantee across the downcast.
+pub trait PinDowncast {
+    fn downcast_pin_ref<T: 'static>(&self) -> Result<Pin<&T>, DowncastError>;
+
+    fn downcast_pin_mut<T: 'static>(&mut self) -> Result<Pin<&mut T>, DowncastError>;
+
+    fn downcast_pin<T: 'static>(self) -> Result<Pin<Box<T>>, DowncastError>;
+}
+
+impl PinDowncast for Pin<Box<dyn Every>> {
+    #[inline]
+    fn downcast_pin_ref<T: 'static>(&self) -> Result<Pin<&T>, DowncastError> {
+        let value = self.as_ref().get_ref().downcast_ref::<T>()?;
+        // SAFETY: the reference points to the same pinned value
+        Ok(unsafe { Pin::new_unchecked(value) })
+    }
+
+    #[inline]
+    fn downcast_pin_mut<T: 'static>(&mut self) -> Result<Pin<&mut T>, DowncastError> {
+        __downcast_pin_mut::<T>(self.as_mut())
+    }
+
+    #[inline]
+    fn downcast_pin<T: 'static>(self) -> Result<Pin<Box<T>>, DowncastError> {
+        if self.is::<T>() {
+            // SAFETY: the value stays in its allocation, which is pinned again straight away
+            let this = unsafe { Pin::into_inner_unchecked(self) };
+            Ok(unsafe { Pin::new_unchecked(__downcast_unchecked::<T>(this)) })
+        } else {
+            Err(cannot_downcast::<T>(self.as_ref().get_ref()))
+        }
+    }
+}
+
+impl PinDowncast for Pin<Box<dyn Every + Send>> {
+    #[inline]
+    fn downcast_pin_ref<T: 'static>(&self) -> Result<Pin<&T>, DowncastError> {
+        let value = self.as_ref().get_ref().downcast_ref::<T>()?;
+        // SAFETY: the reference points to the same pinned value
+        Ok(unsafe { Pin::new_unchecked(value) })
+    }
+
+    #[inline]
+    fn downcast_pin_mut<T: 'static>(&mut self) -> Result<Pin<&mut T>, DowncastError> {
+        __downcast_pin_mut::<T>(self.as_mut())
+    }
+
+    #[inline]
+    fn downcast_pin<T: 'static>(self) -> Result<Pin<Box<T>>, DowncastError> {
+        <Pin<Box<dyn Every>>>::downcast_pin(self)
+    }
+}
+
+impl PinDowncast for Pin<Box<dyn Every + Send + Sync>> {
+    #[inline]
+    fn downcast_pin_ref<T: 'static>(&self) -> Result<Pin<&T>, DowncastError> {
+        let value = self.as_ref().get_ref().downcast_ref::<T>()?;
+        // SAFETY: the reference points to the same pinned value
+        Ok(unsafe { Pin::new_unchecked(value) })
+    }
+
+    #[inline]
+    fn downcast_pin_mut<T: 'static>(&mut self) -> Result<Pin<&mut T>, DowncastError> {
+        __downcast_pin_mut::<T>(self.as_mut())
+    }
+
+    #[inline]
+    fn downcast_pin<T: 'static>(self) -> Result<Pin<Box<T>>, DowncastError> {
+        <Pin<Box<dyn Every>>>::downcast_pin(self)
+    }
+}
+
+#[inline]
+fn __downcast_pin_mut<T: Every>(this: Pin<&mut dyn Every>) -> Result<Pin<&mut T>, DowncastError> {
+    if this.is::<T>() {
+        // SAFETY: just checked the type; the reference points to the same pinned value
+        Ok(unsafe { this.map_unchecked_mut(|this| this.__downcast_mut_unchecked::<T>()) })
+    } else {
+        Err(cannot_downcast::<T>(this.as_ref().get_ref()))
+    }
+}
+
+/// Promotes an owned erased value to shared ownership without downcasting it first. The resulting
+/// pointer retains the erased type (and its auto traits); the value is moved into the shared
+/// allocation.
+pub trait IntoShared {
+    type Target: ?Sized;
+
+    fn into_rc(self) -> Rc<Self::Target>;
+
+    fn into_arc(self) -> Arc<Self::Target>;
+}
+
+impl IntoShared for Box<dyn Every> {
+    type Target = dyn Every;
+
+    #[inline]
+    fn into_rc(self) -> Rc<dyn Every> {
+        Rc::from(self)
+    }
+
+    #[inline]
+    fn into_arc(self) -> Arc<dyn Every> {
+        Arc::from(self)
+    }
+}
+
+impl IntoShared for Box<dyn Every + Send> {
+    type Target = dyn Every + Send;
+
+    #[inline]
+    fn into_rc(self) -> Rc<dyn Every + Send> {
+        Rc::from(self)
+    }
+
+    #[inline]
+    fn into_arc(self) -> Arc<dyn Every + Send> {
+        Arc::from(self)
+    }
+}
+
+impl IntoShared for Box<dyn Every + Send + Sync> {
+    type Target = dyn Every + Send + Sync;
+
+    #[inline]
+    fn into_rc(self) -> Rc<dyn Every + Send + Sync> {
+        Rc::from(self)
+    }
+
+    #[inline]
+    fn into_arc(self) -> Arc<dyn Every + Send + Sync> {
+        Arc::from(self)
+    }
+}
+
+/// Extension methods for `Arc<dyn Every>`.
+pub trait ArcDowncast {
+    fn downcast<T: 'static>(self) -> Result<Arc<T>, DowncastError>;
+}
+
+impl ArcDowncast for Arc<dyn Every> {
+    #[inline]
+    fn downcast<T: 'static>(self) -> Result<Arc<T>, DowncastError> {
+        __downcast_arc::<T>(self).map_err(|this| cannot_downcast::<T>(&*this))
+    }
+}
+
+impl ArcDowncast for Arc<dyn Every + Send> {
+    #[inline]
+    fn downcast<T: 'static>(self) -> Result<Arc<T>, DowncastError> {
+        <Arc<dyn Every>>::downcast(self)
+    }
+}
+
+impl ArcDowncast for Arc<dyn Every + Send + Sync> {
+    #[inline]
+    fn downcast<T: 'static>(self) -> Result<Arc<T>, DowncastError> {
+        <Arc<dyn Every>>::downcast(self)
+    }
+}
+
+#[inline]
+fn __downcast_arc<T: Every>(s: Arc<dyn Every>) -> Result<Arc<T>, Arc<dyn Every>> {
+    if s.is::<T>() {
+        unsafe { Ok(__downcast_arc_unchecked::<T>(s)) }
+    } else {
+        Err(s)
+    }
+}
+
+#[inline]
+unsafe fn __downcast_arc_unchecked<T: Every>(s: Arc<dyn Every>) -> Arc<T> {
+    debug_assert!(s.is::<T>());
+    let raw: *const dyn Every = Arc::into_raw(s);
+    unsafe {
+        Arc::from_raw(raw as *const T)
+    }
+}
+
+/// Extension methods for `Rc<dyn Every>`.
+pub trait RcDowncast {
+    fn downcast<T: 'static>(self) -> Result<Rc<T>, DowncastError>;
+}
+
+impl RcDowncast for Rc<dyn Every> {
+    #[inline]
+    fn downcast<T: 'static>(self) -> Result<Rc<T>, DowncastError> {
+        __downcast_rc::<T>(self).map_err(|this| cannot_downcast::<T>(&*this))
+    }
+}
+
+#[inline]
+fn __downcast_rc<T: Every>(s: Rc<dyn Every>) -> Result<Rc<T>, Rc<dyn Every>> {
+    if s.is::<T>() {
+        unsafe { Ok(__downcast_rc_unchecked::<T>(s)) }
+    } else {
+        Err(s)
+    }
+}
+
+#[inline]
+unsafe fn __downcast_rc_unchecked<T: Every>(s: Rc<dyn Every>) -> Rc<T> {
+    debug_assert!(s.is::<T>());
+    let raw: *const dyn Every = Rc::into_raw(s);
+    unsafe {
+        Rc::from_raw(raw as *const T)
+    }
+}
+
+/// With the `serde` feature enabled, the error may be serialized and deserialized by its type
+/// names. Type IDs are not serialized; a deserialized error carries the [TypeId] of [Unresolved]
+/// in their place, and owns its type names.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DowncastError {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub source_type_id: TypeId,
+    pub source_type_name: Cow<'static, str>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub target_type_id: TypeId,
+    pub target_type_name: Cow<'static, str>,
+
+    /// A [Debug] rendering of the source value, captured if a vtable carrying a [Debug] thunk
+    /// has been specialised for the source type. Excluded from equality comparisons.
+    pub source_debug: Option<String>,
+
+    /// The type, preferably the source type, whose name most closely resembles the target type
+    /// name, if any is close enough to be a likely mix-up. Captured when the error is created, so
+    /// that it renders alike thereafter. Excluded from equality comparisons.
+    pub suggestion: Option<Box<str>>,
+}
+
+/// Stands in for type IDs that could not be resolved, such as after deserializing a [DowncastError].
+pub enum Unresolved {}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DowncastError {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct Repr {
+            source_type_name: String,
+            target_type_name: String,
+            source_debug: Option<String>,
+            suggestion: Option<Box<str>>,
+        }
+
+        let repr = Repr::deserialize(deserializer)?;
+        Ok(Self {
+            source_type_id: TypeId::of::<Unresolved>(),
+            source_type_name: Cow::Owned(repr.source_type_name),
+            target_type_id: TypeId::of::<Unresolved>(),
+            target_type_name: Cow::Owned(repr.target_type_name),
+            source_debug: repr.source_debug,
+            suggestion: repr.suggestion,
+        })
+    }
+}
+
+impl PartialEq for DowncastError {
+    fn eq(&self, other: &Self) -> bool {
+        self.source_type_id == other.source_type_id
+            && self.source_type_name == other.source_type_name
+            && self.target_type_id == other.target_type_id
+            && self.target_type_name == other.target_type_name
+    }
+}
+
+impl Eq for DowncastError {}
+
+/// The alternate form (`{:#}`) renders short type names, with module paths stripped.
+impl Display for DowncastError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            write!(f, "cannot downcast {}", short_type_name(&self.source_type_name))?;
+        } else {
+            write!(f, "cannot downcast {}", self.source_type_name)?;
+        }
+        if let Some(source_debug) = &self.source_debug {
+            write!(f, " (was {source_debug})")?;
+        }
+        if f.alternate() {
+            write!(f, " into {}", short_type_name(&self.target_type_name))?;
+        } else {
+            write!(f, " into {}", self.target_type_name)?;
+        }
+        match &self.suggestion {
+            None => Ok(()),
+            Some(suggestion) if f.alternate() => {
+                write!(f, "; did you mean {}?", short_type_name(suggestion))
+            }
+            Some(suggestion) => write!(f, "; did you mean {suggestion}?"),
+        }
+    }
+}
+
+impl Error for DowncastError {}
+
+pub fn panic<R>(error: impl Error) -> R {
+    panic!("{error}")
+}
+
+/// Like [panic], but panics with the error itself as the payload (rather than a formatted
+/// [String]), so that `catch_unwind` consumers may downcast the payload and inspect its fields.
+pub fn panic_any<R>(error: impl Error + Send + 'static) -> R {
+    std::panic::panic_any(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::every::{
+        downcast_slice, downcast_slice_mut, panic, panic_any, short_type_name, try_from_any,
+        ArcDowncast, BoxDowncast, BoxTryIntoSend, ByAddress, DowncastError, DowncastFilter, Every,
+        IntoShared, PinDowncast, RcDowncast, TypeInfo,
+    };
+    use crate::vtable::{SendMarker, SyncMarker, Token};
+    use std::any::Any;
+    use std::cell::Cell;
+    use std::collections::HashSet;
+    use std::marker::PhantomPinned;
+    use std::pin::Pin;
+    use std::any::TypeId;
+    use std::panic::AssertUnwindSafe;
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use std::{any, panic};
+
+    #[test]
+    fn is() {
+        let val = Box::new(42i32) as Box<dyn Every>;
+        assert!(val.is::<i32>());
+        assert!(!val.is::<&str>());
+    }
+
+    #[test]
+    fn downcast_ref_ok() {
+        let val = Box::new(42i32) as Box<dyn Every>;
+        assert_eq!(Ok(&42i32), val.downcast_ref());
+    }
+
+    #[test]
+    fn downcast_ref_error() {
+        let val = Box::new(42i32) as Box<dyn Every>;
+        assert_eq!(
+            Err(DowncastError {
+                source_type_id: TypeId::of::<i32>(),
+                source_type_name: any::type_name::<i32>().into(),
+                target_type_id: TypeId::of::<&str>(),
+                target_type_name: any::type_name::<&str>().into(),
+                source_debug: None,
+                suggestion: None,
+            }),
+            val.downcast_ref::<&str>()
+        );
+    }
+
+    #[test]
+    fn downcast_mut_ok() {
+        let mut val = Box::new(42i32) as Box<dyn Every>;
+        *val.downcast_mut().unwrap() = 13;
+        assert_eq!(Ok(&13i32), val.downcast_ref());
+    }
+
+    #[test]
+    fn downcast_mut_error() {
+        let mut val = Box::new(42i32) as Box<dyn Every>;
+        assert_eq!(
+            Err(DowncastError {
+                source_type_id: TypeId::of::<i32>(),
+                source_type_name: any::type_name::<i32>().into(),
+                target_type_id: TypeId::of::<&str>(),
+                target_type_name: any::type_name::<&str>().into(),
+                source_debug: None,
+                suggestion: None,
+            }),
+            val.downcast_mut::<&str>()
+        );
+    }
+
+    #[test]
+    fn downcast_ok() {
+        let val = Box::new(42i32) as Box<dyn Every>;
+        assert_eq!(Ok(42i32), val.downcast());
+    }
+
+    #[test]
     fn downcast_error() {
         let val = Box::new(42i32) as Box<dyn Every>;
         assert_eq!(
             Err(DowncastError {
                 source_type_id: TypeId::of::<i32>(),
-                source_type_name: any::type_name::<i32>(),
+                source_type_name: any::type_name::<i32>().into(),
                 target_type_id: TypeId::of::<&str>(),
-                target_type_name: any::type_name::<&str>(),
+                target_type_name: any::type_name::<&str>().into(),
+                source_debug: None,
+                suggestion: None,
             }),
             val.downcast::<&str>()
         );
@@ -304,17 +1176,35 @@ mod tests {
 
     #[test]
     fn downcast_with_panic() {
-        let val = Box::new(42i32) as Box<dyn Every>;
+        let val = Box::new(42i8) as Box<dyn Every>;
         let p = panic::catch_unwind(AssertUnwindSafe(|| {
             let _ = val.downcast_ref::<&str>().unwrap_or_else(panic);
         }));
         let err = p.unwrap_err();
         assert_eq!(
-            "cannot downcast i32 into &str",
+            "cannot downcast i8 into &str",
             err.downcast_ref::<String>().unwrap()
         );
     }
 
+    #[test]
+    fn downcast_with_panic_any() {
+        let val = Box::new(42i8) as Box<dyn Every>;
+        let p = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _ = val.downcast_ref::<&str>().unwrap_or_else(panic_any);
+        }));
+        let err = p.unwrap_err().downcast::<DowncastError>().unwrap();
+        assert_eq!(TypeId::of::<i8>(), err.source_type_id);
+        assert_eq!(TypeId::of::<&str>(), err.target_type_id);
+    }
+
+    #[test]
+    fn downcast_error_without_debug_snapshot() {
+        struct Unregistered;
+        let val = Box::new(Unregistered) as Box<dyn Every>;
+        assert_eq!(None, val.downcast_ref::<&str>().unwrap_err().source_debug);
+    }
+
     #[test]
     fn dyn_any_send_requirement() {
         let val = &mut 42i32 as &mut (dyn Every + Send);
@@ -342,4 +1232,520 @@ mod tests {
         let val = Box::new(42i32) as Box<dyn Every + Send + Sync>;
         assert_eq!(Ok(42i32), val.downcast());
     }
+
+    #[test]
+    fn arc_downcast_ok() {
+        let val = Arc::new(42i32) as Arc<dyn Every>;
+        assert_eq!(Ok(Arc::new(42i32)), val.downcast());
+    }
+
+    #[test]
+    fn arc_downcast_error() {
+        let val = Arc::new(42i32) as Arc<dyn Every>;
+        assert_eq!(
+            Err(DowncastError {
+                source_type_id: TypeId::of::<i32>(),
+                source_type_name: any::type_name::<i32>().into(),
+                target_type_id: TypeId::of::<&str>(),
+                target_type_name: any::type_name::<&str>().into(),
+                source_debug: None,
+                suggestion: None,
+            }),
+            val.downcast::<&str>()
+        );
+    }
+
+    #[test]
+    fn arc_downcast_preserves_sharing() {
+        let val = Arc::new(42i32) as Arc<dyn Every>;
+        let shared = val.clone();
+        let downcast = val.downcast::<i32>().unwrap();
+        assert_eq!(2, Arc::strong_count(&downcast));
+        assert_eq!(Ok(&42i32), shared.downcast_ref());
+    }
+
+    #[test]
+    fn arc_dyn_any_send_requirement() {
+        let val = Arc::new(42i32) as Arc<dyn Every + Send>;
+        assert_eq!(Ok(Arc::new(42i32)), val.downcast());
+    }
+
+    #[test]
+    fn arc_dyn_any_send_sync_requirement() {
+        let val = Arc::new(42i32) as Arc<dyn Every + Send + Sync>;
+        assert_eq!(Ok(Arc::new(42i32)), val.downcast());
+    }
+
+    #[test]
+    fn rc_downcast_ok() {
+        let val = Rc::new(42i32) as Rc<dyn Every>;
+        assert_eq!(Ok(Rc::new(42i32)), val.downcast());
+    }
+
+    #[test]
+    fn rc_downcast_error() {
+        let val = Rc::new(42i32) as Rc<dyn Every>;
+        assert_eq!(
+            Err(DowncastError {
+                source_type_id: TypeId::of::<i32>(),
+                source_type_name: any::type_name::<i32>().into(),
+                target_type_id: TypeId::of::<&str>(),
+                target_type_name: any::type_name::<&str>().into(),
+                source_debug: None,
+                suggestion: None,
+            }),
+            val.downcast::<&str>()
+        );
+    }
+
+    #[test]
+    fn rc_downcast_preserves_sharing() {
+        let val = Rc::new(42i32) as Rc<dyn Every>;
+        let shared = val.clone();
+        let downcast = val.downcast::<i32>().unwrap();
+        assert_eq!(2, Rc::strong_count(&downcast));
+        assert_eq!(Ok(&42i32), shared.downcast_ref());
+    }
+
+    #[test]
+    fn downcast_ref_unchecked() {
+        let val = Box::new(42i32) as Box<dyn Every>;
+        assert_eq!(&42i32, unsafe { val.downcast_ref_unchecked::<i32>() });
+    }
+
+    #[test]
+    fn downcast_mut_unchecked() {
+        let mut val = Box::new(42i32) as Box<dyn Every>;
+        *unsafe { val.downcast_mut_unchecked::<i32>() } = 13;
+        assert_eq!(Ok(&13i32), val.downcast_ref());
+    }
+
+    #[test]
+    fn downcast_unchecked() {
+        let val = Box::new(42i32) as Box<dyn Every>;
+        assert_eq!(42i32, unsafe { val.downcast_unchecked::<i32>() });
+    }
+
+    #[test]
+    fn box_dyn_any_send_sync_downcast_unchecked() {
+        let mut val = Box::new(42i32) as Box<dyn Every + Send + Sync>;
+        assert_eq!(&42i32, unsafe { val.downcast_ref_unchecked::<i32>() });
+        *unsafe { val.downcast_mut_unchecked::<i32>() } = 13;
+        assert_eq!(13i32, unsafe { val.downcast_unchecked::<i32>() });
+    }
+
+    #[test]
+    fn type_info() {
+        let val = Box::new(42u16) as Box<dyn Every>;
+        assert_eq!(
+            TypeInfo {
+                type_id: TypeId::of::<u16>(),
+                type_name: any::type_name::<u16>(),
+                size: 2,
+                align: 2,
+            },
+            (*val).type_info()
+        );
+    }
+
+    #[test]
+    fn type_info_array() {
+        let val: &dyn Every = &[1u32, 2, 3];
+        assert_eq!(12, val.type_info().size);
+        assert_eq!(4, val.type_info().align);
+    }
+
+    #[test]
+    fn short_type_names() {
+        assert_eq!("i32", short_type_name("i32"));
+        assert_eq!("&str", short_type_name("&str"));
+        assert_eq!(
+            "Foo<String>",
+            short_type_name("my_crate::module::submodule::Foo<alloc::string::String>")
+        );
+        assert_eq!(
+            "Option<(i32, Vec<u8>)>",
+            short_type_name("core::option::Option<(i32, alloc::vec::Vec<u8>)>")
+        );
+        assert_eq!("dyn Debug", short_type_name("dyn core::fmt::Debug"));
+        assert_eq!("[Box<u8>; 4]", short_type_name("[alloc::boxed::Box<u8>; 4]"));
+        assert_eq!(
+            "<Foo as Trait>::Assoc",
+            short_type_name("<my_crate::Foo as my_crate::Trait>::Assoc")
+        );
+    }
+
+    #[test]
+    fn type_name_short() {
+        let val = Box::new(String::new()) as Box<dyn Every>;
+        assert_eq!("String", (*val).type_name_short());
+    }
+
+    #[test]
+    fn downcast_error_alternate_display() {
+        let val = Box::new(vec![String::new()]) as Box<dyn Every>;
+        let err = val.downcast_ref::<Option<String>>().unwrap_err();
+        assert_eq!(
+            "cannot downcast alloc::vec::Vec<alloc::string::String> into core::option::Option<alloc::string::String>",
+            format!("{err}")
+        );
+        assert_eq!(
+            "cannot downcast Vec<String> into Option<String>",
+            format!("{err:#}")
+        );
+    }
+
+    #[test]
+    fn try_downcast_ok() {
+        let val = Box::new(42i32) as Box<dyn Every>;
+        assert_eq!(Ok(42i32), val.try_downcast().map_err(|(err, _)| err));
+    }
+
+    #[test]
+    fn try_downcast_error_returns_box() {
+        let val = Box::new(42i32) as Box<dyn Every>;
+        let (err, val) = val.try_downcast::<&str>().unwrap_err();
+        assert_eq!(TypeId::of::<&str>(), err.target_type_id);
+        assert_eq!(Ok(42i32), val.downcast());
+    }
+
+    #[test]
+    fn box_dyn_any_send_sync_try_downcast() {
+        let val = Box::new(42i32) as Box<dyn Every + Send + Sync>;
+        let (_, val): (_, Box<dyn Every + Send + Sync>) = val.try_downcast::<&str>().unwrap_err();
+        assert_eq!(Ok(42i32), val.try_downcast().map_err(|(err, _)| err));
+    }
+
+    struct Pinned(i32, PhantomPinned);
+
+    #[test]
+    fn downcast_pin_ref() {
+        let val = Box::pin(Pinned(42, PhantomPinned)) as Pin<Box<dyn Every>>;
+        assert_eq!(42, val.downcast_pin_ref::<Pinned>().unwrap().0);
+        assert_eq!(
+            TypeId::of::<i32>(),
+            val.downcast_pin_ref::<i32>().unwrap_err().target_type_id
+        );
+    }
+
+    #[test]
+    fn downcast_pin_mut() {
+        let mut val = Box::pin(Pinned(42, PhantomPinned)) as Pin<Box<dyn Every>>;
+        let pinned = val.downcast_pin_mut::<Pinned>().unwrap();
+        unsafe { pinned.get_unchecked_mut().0 = 13 };
+        assert_eq!(13, val.downcast_pin_ref::<Pinned>().unwrap().0);
+        assert!(val.downcast_pin_mut::<i32>().is_err());
+    }
+
+    #[test]
+    fn downcast_pin() {
+        let val = Box::pin(Pinned(42, PhantomPinned)) as Pin<Box<dyn Every>>;
+        let addr = &*val as *const dyn Every as *const ();
+        let pinned = val.downcast_pin::<Pinned>().unwrap();
+        assert_eq!(addr, &*pinned as *const Pinned as *const ());
+        assert_eq!(42, pinned.0);
+    }
+
+    #[test]
+    fn downcast_pin_error() {
+        let val = Box::pin(42i32) as Pin<Box<dyn Every>>;
+        assert_eq!(
+            TypeId::of::<&str>(),
+            val.downcast_pin::<&str>().unwrap_err().target_type_id
+        );
+    }
+
+    #[test]
+    fn pin_box_dyn_any_send_sync_requirement() {
+        let mut val = Box::pin(42i32) as Pin<Box<dyn Every + Send + Sync>>;
+        *val.downcast_pin_mut::<i32>().unwrap() = 13;
+        assert_eq!(&13, &*val.downcast_pin_ref::<i32>().unwrap());
+        assert_eq!(13, *val.downcast_pin::<i32>().unwrap());
+    }
+
+    #[test]
+    fn as_any() {
+        let mut val = Box::new(42i32) as Box<dyn Every>;
+        assert_eq!(Some(&42i32), val.as_any().downcast_ref());
+        *val.as_any_mut().downcast_mut().unwrap() = 13;
+        assert_eq!(Some(&13i32), val.as_any().downcast_ref());
+    }
+
+    #[test]
+    fn box_into_every() {
+        let val: Box<dyn Every> = Box::new(42i32).into();
+        assert_eq!(Ok(42i32), val.downcast());
+    }
+
+    #[test]
+    fn try_from_any_ok() {
+        let val = Box::new(42i32) as Box<dyn Any>;
+        let val = try_from_any::<i32>(val).unwrap();
+        assert_eq!(any::type_name::<i32>(), (*val).type_name());
+    }
+
+    #[test]
+    fn try_from_any_error() {
+        let val = Box::new(42i32) as Box<dyn Any>;
+        let val = try_from_any::<&str>(val).map(|_| ()).unwrap_err();
+        assert_eq!(Some(&42i32), val.downcast_ref());
+    }
+
+    #[test]
+    fn from_any() {
+        use crate::ched::CHED;
+
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Known(u8);
+
+        #[derive(Debug)]
+        struct Unknown;
+
+        drop(CHED::new_auto(Known(0)));
+        let val = crate::every::from_any(Box::new(Known(42))).unwrap();
+        assert_eq!(Ok(Known(42)), val.downcast());
+
+        let val = crate::every::from_any(Box::new(Unknown)).map(|_| ()).unwrap_err();
+        assert!(val.is::<Unknown>());
+    }
+
+    #[test]
+    fn downcast_filter_refs() {
+        let values: Vec<Box<dyn Every>> = vec![Box::new(1i32), Box::new("foo"), Box::new(2i32)];
+        let ints = values.iter().downcast_filter::<i32>().collect::<Vec<_>>();
+        assert_eq!(vec![&1, &2], ints);
+
+        let refs = values.iter().map(|value| &**value).collect::<Vec<&dyn Every>>();
+        let strs = refs.into_iter().downcast_filter::<&str>().collect::<Vec<_>>();
+        assert_eq!(vec![&"foo"], strs);
+    }
+
+    #[test]
+    fn downcast_filter_mut() {
+        let mut values: Vec<Box<dyn Every>> =
+            vec![Box::new(1i32), Box::new("foo"), Box::new(2i32)];
+        for value in values.iter_mut().downcast_filter::<i32>() {
+            *value *= 10;
+        }
+        let ints = values.iter().downcast_filter::<i32>().collect::<Vec<_>>();
+        assert_eq!(vec![&10, &20], ints);
+    }
+
+    #[test]
+    fn downcast_filter_owned() {
+        let values: Vec<Box<dyn Every>> = vec![Box::new(1i32), Box::new("foo"), Box::new(2i32)];
+        let ints = values.into_iter().downcast_filter::<i32>().collect::<Vec<_>>();
+        assert_eq!(vec![1, 2], ints);
+    }
+
+    #[test]
+    fn downcast_slice_ok() {
+        let values: Vec<Box<dyn Every>> = vec![Box::new(1i32), Box::new(2i32)];
+        assert_eq!(Ok(vec![&1i32, &2i32]), downcast_slice::<i32>(&values));
+    }
+
+    #[test]
+    fn downcast_slice_error() {
+        let values: Vec<Box<dyn Every>> = vec![Box::new(1i32), Box::new("foo")];
+        let err = downcast_slice::<i32>(&values).unwrap_err();
+        assert_eq!(TypeId::of::<&str>(), err.source_type_id);
+    }
+
+    #[test]
+    fn downcast_slice_mut_ok() {
+        let mut values: Vec<Box<dyn Every>> = vec![Box::new(1i32), Box::new(2i32)];
+        for value in downcast_slice_mut::<i32>(&mut values).unwrap() {
+            *value += 10;
+        }
+        assert_eq!(Ok(vec![&11i32, &12i32]), downcast_slice::<i32>(&values));
+    }
+
+    #[test]
+    fn downcast_slice_mut_error() {
+        let mut values: Vec<Box<dyn Every>> = vec![Box::new(1i32), Box::new("foo")];
+        let err = downcast_slice_mut::<i32>(&mut values).unwrap_err();
+        assert_eq!(TypeId::of::<&str>(), err.source_type_id);
+    }
+
+    #[test]
+    fn downcast_unsized_ref() {
+        let val = Box::new(Box::<str>::from("foo")) as Box<dyn Every>;
+        assert_eq!(Ok("foo"), val.downcast_unsized_ref::<str>());
+
+        let val = Box::new(&b"foo"[..]) as Box<dyn Every>;
+        assert_eq!(Ok(&b"foo"[..]), val.downcast_unsized_ref::<[u8]>());
+
+        let val = Box::new(Arc::<[u8]>::from(&b"foo"[..])) as Box<dyn Every>;
+        assert_eq!(Ok(&b"foo"[..]), val.downcast_unsized_ref::<[u8]>());
+    }
+
+    #[test]
+    fn downcast_unsized_ref_error() {
+        let val = Box::new(Box::<str>::from("foo")) as Box<dyn Every>;
+        let err = val.downcast_unsized_ref::<[u8]>().unwrap_err();
+        assert_eq!(TypeId::of::<[u8]>(), err.target_type_id);
+        assert_eq!("[u8]", err.target_type_name);
+    }
+
+    #[test]
+    fn downcast_unsized_mut() {
+        let mut val = Box::new(Box::<[u8]>::from(&b"foo"[..])) as Box<dyn Every>;
+        val.downcast_unsized_mut::<[u8]>().unwrap()[0] = b'b';
+        assert_eq!(Ok(&b"boo"[..]), val.downcast_unsized_ref::<[u8]>());
+        assert!(val.downcast_unsized_mut::<str>().is_err());
+    }
+
+    #[test]
+    fn downcast_unsized() {
+        let val = Box::new(Box::<str>::from("foo")) as Box<dyn Every + Send>;
+        assert_eq!(Ok(Box::from("foo")), val.downcast_unsized::<str>());
+    }
+
+    #[test]
+    fn downcast_error_clone() {
+        let val = Box::new(42i8) as Box<dyn Every>;
+        let err = val.downcast_ref::<&str>().unwrap_err();
+        assert_eq!(err, err.clone());
+    }
+
+    #[test]
+    fn downcast_error_suggests_source() {
+        let val = Box::new(42i8) as Box<dyn Every>;
+        let err = val.downcast_ref::<u8>().unwrap_err();
+        assert_eq!(Some("i8"), err.suggestion.as_deref());
+        assert!(err.to_string().ends_with("into u8; did you mean i8?"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn downcast_error_serde() {
+        use crate::every::Unresolved;
+
+        let val = Box::new(42i8) as Box<dyn Every>;
+        let err = val.downcast_ref::<&str>().unwrap_err();
+        let json = serde_json::to_string(&err).unwrap();
+        assert_eq!(
+            concat!(
+                r#"{"source_type_name":"i8","target_type_name":"&str","#,
+                r#""source_debug":null,"suggestion":null}"#
+            ),
+            json
+        );
+
+        let deserialized = serde_json::from_str::<DowncastError>(&json).unwrap();
+        assert_eq!(TypeId::of::<Unresolved>(), deserialized.source_type_id);
+        assert_eq!(TypeId::of::<Unresolved>(), deserialized.target_type_id);
+        assert_eq!(err.to_string(), deserialized.to_string());
+
+        // the suggestion travels with the error, rather than being looked up by the recipient
+        let err = val.downcast_ref::<u8>().unwrap_err();
+        let deserialized =
+            serde_json::from_str::<DowncastError>(&serde_json::to_string(&err).unwrap()).unwrap();
+        assert_eq!(Some("i8"), deserialized.suggestion.as_deref());
+        assert_eq!(err.to_string(), deserialized.to_string());
+
+        // arbitrary names are owned by the error, rather than leaked
+        let json =
+            r#"{"source_type_name":"no::Such","target_type_name":"&str","source_debug":null}"#;
+        let deserialized = serde_json::from_str::<DowncastError>(json).unwrap();
+        assert!(matches!(deserialized.source_type_name, std::borrow::Cow::Owned(_)));
+        assert_eq!("no::Such", deserialized.source_type_name);
+    }
+
+    #[test]
+    fn as_ptr() {
+        let mut val = Box::new(42i32) as Box<dyn Every>;
+        let addr = &*val as *const dyn Every as *const i32;
+        assert_eq!(addr, val.as_ptr().cast::<i32>());
+        assert_eq!(addr, val.as_mut_ptr().cast::<i32>() as *const i32);
+        unsafe { *val.as_mut_ptr().cast::<i32>() = 13 };
+        assert_eq!(Ok(&13i32), val.downcast_ref());
+    }
+
+    #[test]
+    fn try_into_send() {
+        struct Sendable;
+        let _ = Token::<Sendable, SendMarker>::default();
+
+        let val = Box::new(Sendable) as Box<dyn Every>;
+        let val = val.try_into_send().map_err(|_| ()).unwrap();
+        assert!(val.is::<Sendable>());
+        let val = val as Box<dyn Every>;
+        assert!(val.try_into_sync().is_err());
+    }
+
+    #[test]
+    fn try_into_sync() {
+        struct Shareable;
+        let _ = Token::<Shareable, SyncMarker>::default();
+
+        let val = Box::new(Shareable) as Box<dyn Every>;
+        let val = val.try_into_send().map_err(|_| ()).unwrap() as Box<dyn Every>;
+        let val = val.try_into_sync().map_err(|_| ()).unwrap();
+        std::thread::spawn(move || assert!(val.is::<Shareable>())).join().unwrap();
+    }
+
+    #[test]
+    fn try_into_send_unrecorded() {
+        let val = Box::new(Cell::new(42i32)) as Box<dyn Every>;
+        let val = val.try_into_send().map(|_| ()).unwrap_err();
+        assert!(val.try_into_sync().is_err());
+    }
+
+    #[test]
+    fn downcast_ref_or() {
+        let val = Box::new(42i32) as Box<dyn Every>;
+        assert_eq!(&42i32, val.downcast_ref_or(&13i32));
+        assert_eq!(&"foo", val.downcast_ref_or(&"foo"));
+        assert_eq!(&"bar", val.downcast_ref_or_else(|| &"bar"));
+    }
+
+    #[test]
+    fn downcast_or() {
+        let val = Box::new(42i32) as Box<dyn Every>;
+        assert_eq!("foo", val.downcast_or("foo"));
+        let val = Box::new(42i32) as Box<dyn Every + Send + Sync>;
+        assert_eq!(42i32, val.downcast_or_else::<i32>(|| unreachable!()));
+    }
+
+    #[test]
+    fn by_address() {
+        struct Opaque(#[allow(dead_code)] i32);
+        let first = Box::new(Opaque(42)) as Box<dyn Every>;
+        let second = Box::new(Opaque(42)) as Box<dyn Every>;
+
+        let mut set = HashSet::new();
+        assert!(set.insert(ByAddress(&*first)));
+        assert!(set.insert(ByAddress(&*second)));
+        assert!(!set.insert(ByAddress(&*first)));
+        assert_eq!(ByAddress(&*first), ByAddress(&*first));
+        assert_ne!(ByAddress(&*first), ByAddress(&*second));
+    }
+
+    #[test]
+    fn by_address_owned() {
+        let first = ByAddress(Box::new(42i32) as Box<dyn Every>);
+        let addr = first.addr();
+        let mut set = HashSet::new();
+        set.insert(first);
+        let first = set.into_iter().next().unwrap();
+        assert_eq!(addr, first.addr());
+        assert_eq!(Ok(&42i32), first.downcast_ref());
+    }
+
+    #[test]
+    fn into_rc() {
+        let val = (Box::new(42i32) as Box<dyn Every>).into_rc();
+        assert_eq!(Ok(Rc::new(42i32)), val.downcast());
+    }
+
+    #[test]
+    fn into_arc() {
+        let val = (Box::new(42i32) as Box<dyn Every + Send + Sync>).into_arc();
+        let shared = val.clone();
+        std::thread::spawn(move || assert_eq!(Ok(&42i32), shared.downcast_ref()))
+            .join()
+            .unwrap();
+        assert_eq!(Ok(Arc::new(42i32)), val.downcast());
+    }
 }
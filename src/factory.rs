@@ -0,0 +1,238 @@
+//! Creating `dyn Every` values from type names known only at runtime.
+//!
+//! A constructor is registered per type with [register] (for [Default] types) or
+//! [register_parse] (for [FromStr] types). Thereafter, [create_by_name] and [parse_by_name]
+//! instantiate the type given its name, as reported by [`std::any::type_name()`]. Where a
+//! [ched::VTable] has also been specialised for the type, [create_ched_by_name] yields a [CHED].
+//!
+//! With the `serde` feature enabled, a deserializer may also be registered with
+//! [register_deserialize], reconstructing values of the named type from [Content].
+
+use crate::ched;
+use crate::ched::CHED;
+#[cfg(feature = "serde")]
+use crate::content::{Content, ContentError};
+use crate::every::Every;
+use crate::vtable;
+use std::any;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::sync::{LazyLock, RwLock};
+
+type CreateFn = fn() -> Box<dyn Every>;
+
+type ParseFn = fn(&str) -> Result<Box<dyn Every>, Box<dyn Error + Send + Sync>>;
+
+fn create<T: Default + 'static>() -> Box<dyn Every> {
+    Box::new(T::default())
+}
+
+fn parse<T: FromStr + 'static>(input: &str) -> Result<Box<dyn Every>, Box<dyn Error + Send + Sync>>
+where
+    T::Err: Error + Send + Sync + 'static,
+{
+    let value = input.parse::<T>()?;
+    Ok(Box::new(value))
+}
+
+#[cfg(feature = "serde")]
+type DeserializeFn = fn(Content) -> Result<Box<dyn Every>, ContentError>;
+
+#[cfg(feature = "serde")]
+fn deserialize<T: serde::de::DeserializeOwned + 'static>(
+    content: Content,
+) -> Result<Box<dyn Every>, ContentError> {
+    let value = T::deserialize(content)?;
+    Ok(Box::new(value))
+}
+
+#[derive(Default, Clone, Copy)]
+struct Factory {
+    create: Option<CreateFn>,
+    parse: Option<ParseFn>,
+    #[cfg(feature = "serde")]
+    deserialize: Option<DeserializeFn>,
+}
+
+#[derive(Default)]
+struct Registry {
+    factories: RwLock<HashMap<&'static str, Factory>>,
+}
+
+impl Registry {
+    fn singleton() -> &'static Registry {
+        static LAZY: LazyLock<Registry> = LazyLock::new(Default::default);
+        &LAZY
+    }
+
+    fn update<T: 'static>(&self, update: impl FnOnce(&mut Factory)) {
+        let mut factories = self.factories.write().unwrap();
+        update(factories.entry(any::type_name::<T>()).or_default());
+    }
+
+    fn get(&self, type_name: &str) -> Option<Factory> {
+        self.factories.read().unwrap().get(type_name).copied()
+    }
+}
+
+/// The reason a value could not be created by name.
+#[derive(Debug)]
+pub enum FactoryError {
+    /// No suitable constructor has been registered for the named type.
+    Unregistered(String),
+
+    /// A [CHED] was requested, but no [ched::VTable] has been specialised for the named type.
+    Unspecialised(String),
+
+    /// The input could not be parsed as a value of the named type.
+    Parse(Box<dyn Error + Send + Sync>),
+}
+
+impl Display for FactoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FactoryError::Unregistered(type_name) => {
+                write!(f, "no constructor registered for {type_name}")
+            }
+            FactoryError::Unspecialised(type_name) => {
+                write!(f, "no vtable specialised for {type_name}")
+            }
+            FactoryError::Parse(err) => write!(f, "cannot parse input: {err}"),
+        }
+    }
+}
+
+impl Error for FactoryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FactoryError::Parse(err) => Some(&**err),
+            _ => None,
+        }
+    }
+}
+
+/// Registers [`T::default()`](Default::default) as the constructor of [T].
+pub fn register<T: Default + 'static>() {
+    Registry::singleton().update::<T>(|factory| factory.create = Some(create::<T>));
+}
+
+/// Registers [`T::from_str()`](FromStr::from_str) as the parser of [T].
+pub fn register_parse<T: FromStr + 'static>()
+where
+    T::Err: Error + Send + Sync + 'static,
+{
+    Registry::singleton().update::<T>(|factory| factory.parse = Some(parse::<T>));
+}
+
+/// Registers [`T::deserialize()`](serde::Deserialize::deserialize) as the deserializer of [T].
+#[cfg(feature = "serde")]
+pub fn register_deserialize<T: serde::de::DeserializeOwned + 'static>() {
+    Registry::singleton().update::<T>(|factory| factory.deserialize = Some(deserialize::<T>));
+}
+
+/// Creates a value of the named type using the constructor registered with [register].
+pub fn create_by_name(type_name: &str) -> Result<Box<dyn Every>, FactoryError> {
+    let create = Registry::singleton().get(type_name).and_then(|factory| factory.create);
+    let create = create.ok_or_else(|| FactoryError::Unregistered(type_name.into()))?;
+    Ok(create())
+}
+
+/// Parses `input` as a value of the named type using the parser registered with
+/// [register_parse].
+pub fn parse_by_name(type_name: &str, input: &str) -> Result<Box<dyn Every>, FactoryError> {
+    let parse = Registry::singleton().get(type_name).and_then(|factory| factory.parse);
+    let parse = parse.ok_or_else(|| FactoryError::Unregistered(type_name.into()))?;
+    parse(input).map_err(FactoryError::Parse)
+}
+
+/// Creates a [CHED] of the named type using the constructor registered with [register] and the
+/// [ched::VTable] specialised for the type in the singleton [Registry](vtable::Registry).
+pub fn create_ched_by_name(type_name: &str) -> Result<CHED, FactoryError> {
+    let vtable = vtable::Registry::singleton()
+        .resolve::<ched::VTable>(type_name)
+        .ok_or_else(|| FactoryError::Unspecialised(type_name.into()))?;
+    let value = create_by_name(type_name)?;
+    Ok(CHED::from_parts(value, vtable))
+}
+
+/// Reconstructs a value of the named type from `content` using the deserializer registered with
+/// [register_deserialize].
+#[cfg(feature = "serde")]
+pub fn deserialize_by_name(
+    type_name: &str,
+    content: Content,
+) -> Result<Box<dyn Every>, FactoryError> {
+    let deserialize = Registry::singleton().get(type_name).and_then(|factory| factory.deserialize);
+    let deserialize = deserialize.ok_or_else(|| FactoryError::Unregistered(type_name.into()))?;
+    deserialize(content).map_err(|err| FactoryError::Parse(err.into()))
+}
+
+/// Reconstructs a [CHED] of the named type using the deserializer registered with
+/// [register_deserialize] and the [ched::VTable] specialised for the type in the singleton
+/// [Registry](vtable::Registry).
+#[cfg(feature = "serde")]
+pub fn deserialize_ched_by_name(type_name: &str, content: Content) -> Result<CHED, FactoryError> {
+    let vtable = vtable::Registry::singleton()
+        .resolve::<ched::VTable>(type_name)
+        .ok_or_else(|| FactoryError::Unspecialised(type_name.into()))?;
+    let value = deserialize_by_name(type_name, content)?;
+    Ok(CHED::from_parts(value, vtable))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ched::Token;
+    use crate::factory::{
+        create_by_name, create_ched_by_name, parse_by_name, register, register_parse,
+        FactoryError,
+    };
+    use std::any;
+
+    #[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+    struct Config {
+        retries: u8,
+    }
+
+    #[test]
+    fn create_registered() {
+        register::<Config>();
+        let value = create_by_name(any::type_name::<Config>()).unwrap();
+        assert_eq!(Ok(&Config::default()), value.downcast_ref());
+    }
+
+    #[test]
+    fn create_unregistered() {
+        let err = create_by_name("Unregistered").map(|_| ()).unwrap_err();
+        assert!(matches!(err, FactoryError::Unregistered(_)));
+        assert_eq!("no constructor registered for Unregistered", err.to_string());
+    }
+
+    #[test]
+    fn parse_registered() {
+        register_parse::<u16>();
+        let value = parse_by_name("u16", "42").unwrap();
+        assert_eq!(Ok(&42u16), value.downcast_ref());
+
+        let err = parse_by_name("u16", "forty-two").map(|_| ()).unwrap_err();
+        assert!(matches!(err, FactoryError::Parse(_)));
+        assert_eq!("cannot parse input: invalid digit found in string", err.to_string());
+    }
+
+    #[test]
+    fn create_ched() {
+        #[derive(Default)]
+        struct Plain;
+
+        register::<Plain>();
+        let err = create_ched_by_name(any::type_name::<Plain>()).unwrap_err();
+        assert!(matches!(err, FactoryError::Unspecialised(_)));
+
+        register::<Config>();
+        let _ = Token::<Config>::default();
+        let obj = create_ched_by_name(any::type_name::<Config>()).unwrap();
+        assert_eq!(obj, obj.clone());
+        assert_eq!("Config { retries: 0 }", format!("{obj:?}"));
+    }
+}
@@ -0,0 +1,158 @@
+//! A dynamic object for values implementing [Error], which is itself an [Error].
+//!
+//! [DynError] exposes the [Debug], [Display] and [`source()`](Error::source) of the wrapped error
+//! through its vtable, so that heterogeneous errors may be aggregated without losing the ability
+//! to downcast them to their original types.
+
+use crate::every::{BoxDowncast, DowncastError, Every};
+use crate::vtable::Specialise;
+use crate::{debug, display, source, vtable, DebugFn, DisplayFn, SourceFn};
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+
+pub type Token<T> = vtable::Token<T, VTable>;
+
+pub struct DynError {
+    inner: Box<dyn Every>,
+    vtable: &'static VTable,
+}
+
+impl DynError {
+    #[inline]
+    pub fn new<T: 'static>(error: T, tok: &Token<T>) -> Self {
+        Self {
+            inner: Box::new(error),
+            vtable: tok.vtable_ref(),
+        }
+    }
+
+    /// Constructs a [DynError], acquiring the token for [T] from the global registry. Being
+    /// itself an [Error], [DynError] admits no `From<T: Error>` conversion, hence the constructor.
+    #[inline]
+    pub fn new_auto<T: Error + 'static>(error: T) -> Self {
+        Self::new(error, vtable::global_token())
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &dyn Every {
+        &*self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Box<dyn Every> {
+        self.inner
+    }
+
+    /// The name of the wrapped error's type.
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        (*self.inner).type_name()
+    }
+
+    #[inline]
+    pub fn is<T: 'static>(&self) -> bool {
+        self.inner.is::<T>()
+    }
+
+    #[inline]
+    pub fn downcast_ref<T: 'static>(&self) -> Result<&T, DowncastError> {
+        self.inner.downcast_ref()
+    }
+
+    #[inline]
+    pub fn downcast<T: 'static>(self) -> Result<T, DowncastError> {
+        self.inner.downcast()
+    }
+}
+
+pub struct VTable {
+    debug: DebugFn,
+    display: DisplayFn,
+    source: SourceFn,
+}
+
+impl VTable {
+    pub const fn of<T: Error + 'static>() -> Self {
+        Self {
+            debug: debug::<T>,
+            display: display::<T>,
+            source: source::<T>,
+        }
+    }
+}
+
+impl<T: Error + 'static> Specialise<T> for VTable {
+    fn specialise() -> Self {
+        Self::of::<T>()
+    }
+
+    fn debug_fn() -> Option<DebugFn> {
+        Some(debug::<T>)
+    }
+}
+
+impl Debug for DynError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable.debug)(&*self.inner, f)
+    }
+}
+
+impl Display for DynError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.vtable.display)(&*self.inner, f)
+    }
+}
+
+impl Error for DynError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        (self.vtable.source)(&*self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fault::DynError;
+    use std::error::Error;
+    use std::fmt::{Display, Formatter};
+    use std::num::ParseIntError;
+
+    #[derive(Debug)]
+    struct Wrapped(ParseIntError);
+
+    impl Display for Wrapped {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "wrapped: {}", self.0)
+        }
+    }
+
+    impl Error for Wrapped {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn forwards_error() {
+        let cause = "x".parse::<u8>().unwrap_err();
+        let err = DynError::new_auto(Wrapped(cause.clone()));
+        assert_eq!("wrapped: invalid digit found in string", err.to_string());
+        assert!(format!("{err:?}").starts_with("Wrapped(ParseIntError"));
+        let source = err.source().unwrap();
+        assert_eq!(Some(&cause), source.downcast_ref::<ParseIntError>());
+
+        assert!(DynError::new_auto(cause).source().is_none());
+    }
+
+    #[test]
+    fn aggregate_and_downcast() {
+        let errors = [
+            DynError::new_auto("x".parse::<u8>().unwrap_err()),
+            DynError::new_auto(std::fmt::Error),
+        ];
+        assert!(errors[0].is::<ParseIntError>());
+        assert_eq!(Ok(&std::fmt::Error), errors[1].downcast_ref());
+
+        let err = errors[0].downcast_ref::<std::fmt::Error>().unwrap_err();
+        assert_eq!("core::num::error::ParseIntError", err.source_type_name);
+    }
+}
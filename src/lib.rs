@@ -1,12 +1,30 @@
-use std::fmt::{Debug, Formatter};
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
 use std::hash::{Hash, Hasher};
-use crate::every::{panic, Every};
+use crate::every::{panic, DowncastError, Every};
 
+pub mod cast;
 pub mod ched;
+#[cfg(feature = "serde")]
+pub mod content;
+pub mod diff;
 pub mod every;
+pub mod factory;
+pub mod fault;
+pub mod pool;
+pub mod probe;
+pub mod scoped;
+pub mod size;
+pub mod stable;
+pub mod visit;
 pub mod vtable;
 
-type PartialEqFn = fn(&dyn Every, &dyn Every) -> bool;
+#[cfg(feature = "inventory")]
+#[doc(hidden)]
+pub use inventory;
+
+pub type PartialEqFn = fn(&dyn Every, &dyn Every) -> bool;
 
 pub fn partial_eq<T: PartialEq + 'static>(this: &dyn Every, other: &dyn Every) -> bool {
     let lhs = this.downcast_ref::<T>().unwrap_or_else(panic);
@@ -14,7 +32,31 @@ pub fn partial_eq<T: PartialEq + 'static>(this: &dyn Every, other: &dyn Every) -
     rhs.is_ok_and(|rhs| lhs == rhs)
 }
 
-type DebugFn = fn(&dyn Every, &mut Formatter<'_>) -> Result<(), core::fmt::Error>;
+pub type PartialOrdFn = fn(&dyn Every, &dyn Every) -> Option<Ordering>;
+
+/// Values of different types are incomparable.
+pub fn partial_cmp<T: PartialOrd + 'static>(
+    this: &dyn Every,
+    other: &dyn Every,
+) -> Option<Ordering> {
+    let lhs = this.downcast_ref::<T>().unwrap_or_else(panic);
+    let rhs = other.downcast_ref::<T>().ok()?;
+    lhs.partial_cmp(rhs)
+}
+
+pub type OrdFn = fn(&dyn Every, &dyn Every) -> Ordering;
+
+/// Values of different types are ordered by their [TypeId](std::any::TypeId), which is consistent
+/// within, but not across, builds of a program.
+pub fn cmp<T: Ord + 'static>(this: &dyn Every, other: &dyn Every) -> Ordering {
+    let lhs = this.downcast_ref::<T>().unwrap_or_else(panic);
+    match other.downcast_ref::<T>() {
+        Ok(rhs) => lhs.cmp(rhs),
+        Err(_) => this.type_id().cmp(&other.type_id()),
+    }
+}
+
+pub type DebugFn = fn(&dyn Every, &mut Formatter<'_>) -> Result<(), core::fmt::Error>;
 
 pub fn debug<T: Debug + 'static>(
     this: &dyn Every,
@@ -24,6 +66,46 @@ pub fn debug<T: Debug + 'static>(
     value.fmt(f)
 }
 
+/// Renders any value as `<redacted {type}>`, in place of a [Debug] or [Display] thunk, for values
+/// such as credentials that must never be logged.
+pub fn redacted(this: &dyn Every, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+    write!(f, "<redacted {}>", this.type_name())
+}
+
+pub type DisplayFn = fn(&dyn Every, &mut Formatter<'_>) -> Result<(), core::fmt::Error>;
+
+pub fn display<T: Display + 'static>(
+    this: &dyn Every,
+    f: &mut Formatter<'_>,
+) -> Result<(), core::fmt::Error> {
+    let value = this.downcast_ref::<T>().unwrap_or_else(panic);
+    value.fmt(f)
+}
+
+pub type SourceFn = fn(&dyn Every) -> Option<&(dyn Error + 'static)>;
+
+pub fn source<T: Error + 'static>(this: &dyn Every) -> Option<&(dyn Error + 'static)> {
+    let value = this.downcast_ref::<T>().unwrap_or_else(panic);
+    value.source()
+}
+
+pub type DeepSizeFn = fn(&dyn Every) -> usize;
+
+pub fn deep_size<T: size::DeepSize + 'static>(this: &dyn Every) -> usize {
+    let value = this.downcast_ref::<T>().unwrap_or_else(panic);
+    value.deep_size_of_children()
+}
+
+pub type DiffFn = fn(&dyn Every, &dyn Every) -> diff::DiffReport;
+
+pub fn diff<T: diff::DynDiff + 'static>(this: &dyn Every, other: &dyn Every) -> diff::DiffReport {
+    let lhs = this.downcast_ref::<T>().unwrap_or_else(panic);
+    let rhs = other.downcast_ref::<T>().unwrap_or_else(panic);
+    let mut report = diff::DiffReport::new();
+    lhs.diff(rhs, &mut report);
+    report
+}
+
 pub type CloneFn = fn(&dyn Every) -> Box<dyn Every>;
 
 pub fn clone<T: Clone + 'static>(this: &dyn Every) -> Box<dyn Every> {
@@ -32,9 +114,195 @@ pub fn clone<T: Clone + 'static>(this: &dyn Every) -> Box<dyn Every> {
     Box::new(cloned)
 }
 
+pub type TryCloneFn = fn(&dyn Every) -> Result<Box<dyn Every>, ched::CloneError>;
+
+pub fn try_clone<T: ched::TryClone + 'static>(
+    this: &dyn Every,
+) -> Result<Box<dyn Every>, ched::CloneError> {
+    let value = this.downcast_ref::<T>().unwrap_or_else(panic);
+    let cloned = value.try_clone()?;
+    Ok(Box::new(cloned))
+}
+
 pub type HashFn = fn(&dyn Every, &mut dyn Hasher);
 
 pub fn hash<T: Hash + 'static>(this: &dyn Every, mut state: &mut dyn Hasher) {
     let this = this.downcast_ref::<T>().unwrap_or_else(panic);
     this.hash(&mut state);
 }
+
+#[cfg(feature = "serde")]
+pub type SerializeFn = fn(&dyn Every) -> Result<content::Content, content::ContentError>;
+
+#[cfg(feature = "serde")]
+pub fn serialize<T: serde::Serialize + 'static>(
+    this: &dyn Every,
+) -> Result<content::Content, content::ContentError> {
+    let value = this.downcast_ref::<T>().unwrap_or_else(panic);
+    content::Content::of(value)
+}
+
+/// A drop hook, for [`VTable::with_drop_hook()`](ched::VTable::with_drop_hook), that wipes the
+/// value's secret material.
+#[cfg(feature = "zeroize")]
+pub fn zeroize<T: ::zeroize::Zeroize + 'static>(this: &mut dyn Every) {
+    let value = this.downcast_mut::<T>().unwrap_or_else(panic);
+    value.zeroize();
+}
+
+// Counterparts of the standard thunks that surface a value of the wrong type as a
+// DowncastError rather than panicking, for vtables that choose their own failure policy.
+
+pub type CheckedPartialEqFn = fn(&dyn Every, &dyn Every) -> Result<bool, DowncastError>;
+
+/// As per [partial_eq], with `other` of a different type deemed unequal.
+pub fn checked_partial_eq<T: PartialEq + 'static>(
+    this: &dyn Every,
+    other: &dyn Every,
+) -> Result<bool, DowncastError> {
+    let lhs = this.downcast_ref::<T>()?;
+    let rhs = other.downcast_ref::<T>();
+    Ok(rhs.is_ok_and(|rhs| lhs == rhs))
+}
+
+/// The reason a checked formatting thunk could not render a value.
+#[derive(Debug, PartialEq)]
+pub enum CheckedFmtError {
+    /// The value is not of the type the thunk was specialised for.
+    Downcast(DowncastError),
+
+    /// The value's own formatting failed.
+    Fmt(core::fmt::Error),
+}
+
+impl Display for CheckedFmtError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckedFmtError::Downcast(err) => Display::fmt(err, f),
+            CheckedFmtError::Fmt(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl Error for CheckedFmtError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CheckedFmtError::Downcast(err) => Some(err),
+            CheckedFmtError::Fmt(err) => Some(err),
+        }
+    }
+}
+
+impl From<DowncastError> for CheckedFmtError {
+    #[inline]
+    fn from(err: DowncastError) -> Self {
+        Self::Downcast(err)
+    }
+}
+
+impl From<core::fmt::Error> for CheckedFmtError {
+    #[inline]
+    fn from(err: core::fmt::Error) -> Self {
+        Self::Fmt(err)
+    }
+}
+
+pub type CheckedDebugFn = fn(&dyn Every, &mut Formatter<'_>) -> Result<(), CheckedFmtError>;
+
+pub fn checked_debug<T: Debug + 'static>(
+    this: &dyn Every,
+    f: &mut Formatter<'_>,
+) -> Result<(), CheckedFmtError> {
+    let value = this.downcast_ref::<T>()?;
+    Ok(value.fmt(f)?)
+}
+
+pub type CheckedCloneFn = fn(&dyn Every) -> Result<Box<dyn Every>, DowncastError>;
+
+pub fn checked_clone<T: Clone + 'static>(
+    this: &dyn Every,
+) -> Result<Box<dyn Every>, DowncastError> {
+    let value = this.downcast_ref::<T>()?;
+    Ok(Box::new(value.clone()))
+}
+
+pub type CheckedHashFn = fn(&dyn Every, &mut dyn Hasher) -> Result<(), DowncastError>;
+
+pub fn checked_hash<T: Hash + 'static>(
+    this: &dyn Every,
+    mut state: &mut dyn Hasher,
+) -> Result<(), DowncastError> {
+    let value = this.downcast_ref::<T>()?;
+    value.hash(&mut state);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::every::BoxDowncast;
+    use crate::{
+        checked_clone, checked_debug, checked_hash, checked_partial_eq, cmp, partial_cmp,
+        CheckedCloneFn, CheckedDebugFn, CheckedFmtError, CheckedPartialEqFn, OrdFn, PartialOrdFn,
+    };
+    use crate::every::Every;
+    use std::cell::RefCell;
+    use std::cmp::Ordering;
+    use std::fmt::{Debug, Formatter, Write};
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    #[test]
+    fn checked_thunks() {
+        let eq: CheckedPartialEqFn = checked_partial_eq::<u32>;
+        assert_eq!(Ok(true), eq(&42u32, &42u32));
+        assert_eq!(Ok(false), eq(&42u32, &42i32));
+        let err = eq(&42i32, &42u32).unwrap_err();
+        assert_eq!(std::any::type_name::<u32>(), err.target_type_name);
+
+        let clone: CheckedCloneFn = checked_clone::<String>;
+        assert_eq!(Ok(String::from("foo")), clone(&String::from("foo")).unwrap().downcast());
+        assert!(clone(&42u32).is_err());
+
+        let mut hasher = DefaultHasher::new();
+        assert_eq!(Ok(()), checked_hash::<u32>(&42u32, &mut hasher));
+        let mut expected = DefaultHasher::new();
+        42u32.hash(&mut expected);
+        assert_eq!(expected.finish(), hasher.finish());
+        assert!(checked_hash::<u32>(&42i32, &mut hasher).is_err());
+
+        struct Checked(Box<dyn Every>, RefCell<Option<CheckedFmtError>>);
+
+        impl Debug for Checked {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                let debug: CheckedDebugFn = checked_debug::<u32>;
+                debug(&*self.0, f).map_err(|err| {
+                    *self.1.borrow_mut() = Some(err);
+                    std::fmt::Error
+                })
+            }
+        }
+
+        assert_eq!("42", format!("{:?}", Checked(Box::new(42u32), RefCell::default())));
+
+        let checked = Checked(Box::new(42i32), RefCell::default());
+        assert!(write!(String::new(), "{checked:?}").is_err());
+        let Some(CheckedFmtError::Downcast(err)) = checked.1.take() else {
+            panic!("expected a downcast error");
+        };
+        assert_eq!(std::any::type_name::<u32>(), err.target_type_name);
+    }
+
+    #[test]
+    fn ordering_thunks() {
+        let partial_cmp: PartialOrdFn = partial_cmp::<f64>;
+        assert_eq!(Some(Ordering::Less), partial_cmp(&1.0, &2.0));
+        assert_eq!(None, partial_cmp(&1.0, &f64::NAN));
+        assert_eq!(None, partial_cmp(&1.0, &1u32));
+
+        let cmp: OrdFn = cmp::<u32>;
+        assert_eq!(Ordering::Greater, cmp(&2u32, &1u32));
+        assert_eq!(Ordering::Equal, cmp(&2u32, &2u32));
+        let mixed = cmp(&2u32, &"two");
+        assert_ne!(Ordering::Equal, mixed);
+        assert_eq!(mixed.reverse(), crate::cmp::<&str>(&"two", &2u32));
+    }
+}
@@ -0,0 +1,207 @@
+//! Recycling the heap allocations of dynamic objects.
+//!
+//! A [Pool] retains the blocks of dropped values, keyed by their size class, for reuse by
+//! subsequent values of the same size and alignment. Each block is prefixed by a header referring
+//! back to its pool, so that a pooled value can be released without the pool being named again.
+//!
+//! With the `allocator_api` feature, blocks may instead be drawn from a caller-provided
+//! [Allocator], such as an arena or slab.
+
+use std::alloc::{self, Layout};
+use std::collections::HashMap;
+use std::mem;
+use std::ptr::NonNull;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+#[derive(Clone, Default)]
+pub struct Pool(Arc<Inner>);
+
+struct Inner {
+    limit: usize,
+    free: Mutex<HashMap<Layout, Vec<Block>>>,
+}
+
+impl Default for Inner {
+    fn default() -> Self {
+        Self {
+            limit: usize::MAX,
+            free: Mutex::default(),
+        }
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        let free = self.free.get_mut().unwrap_or_else(PoisonError::into_inner);
+        for (layout, blocks) in free.drain() {
+            for Block(base) in blocks {
+                // SAFETY: the block was allocated with this layout and is no longer in use
+                unsafe { alloc::dealloc(base.as_ptr(), layout) };
+            }
+        }
+    }
+}
+
+/// An idle block.
+struct Block(NonNull<u8>);
+
+// SAFETY: an idle block is exclusively owned by the pool
+unsafe impl Send for Block {}
+
+/// A source of memory for values constructed with [`CHED::new_in()`](crate::ched::CHED::new_in),
+/// standing in for the unstable `std::alloc::Allocator`.
+///
+/// # Safety
+/// A block returned by [`allocate()`](Self::allocate) must be valid for the requested layout,
+/// and remain so until passed to [`deallocate()`](Self::deallocate).
+#[cfg(feature = "allocator_api")]
+pub unsafe trait Allocator {
+    /// Allocates a block for the given layout, which is never zero-sized, or returns [None] if
+    /// memory is exhausted.
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// # Safety
+    /// [ptr] must have been returned by [`allocate()`](Self::allocate) for the same layout.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+}
+
+/// Where a block is obtained from and returned to.
+#[derive(Clone)]
+enum Owner {
+    Pool(Pool),
+    #[cfg(feature = "allocator_api")]
+    Allocator(Arc<dyn Allocator>),
+}
+
+/// Immediately precedes each pooled value.
+struct Header {
+    owner: Owner,
+    layout: Layout,
+}
+
+/// The layout of the block holding a value of the given layout, along with the value's offset.
+fn block_layout(value: Layout) -> (Layout, usize) {
+    let (block, offset) = Layout::new::<Header>().extend(value).expect("layout overflow");
+    (block.pad_to_align(), offset)
+}
+
+/// Locates the header of a pooled value.
+///
+/// # Safety
+/// [slot] must have been returned by [`Owner::allocate()`].
+unsafe fn header(slot: NonNull<u8>) -> NonNull<Header> {
+    unsafe { slot.sub(mem::size_of::<Header>()).cast() }
+}
+
+impl Pool {
+    /// Constructs a pool that retains every released block.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a pool that retains at most `limit` idle blocks per size class, freeing any
+    /// released beyond that.
+    pub fn with_limit(limit: usize) -> Self {
+        Self(Arc::new(Inner {
+            limit,
+            free: Mutex::default(),
+        }))
+    }
+
+    /// The number of idle blocks awaiting reuse, across all size classes.
+    pub fn idle(&self) -> usize {
+        self.free().values().map(Vec::len).sum()
+    }
+
+    fn free(&self) -> MutexGuard<'_, HashMap<Layout, Vec<Block>>> {
+        self.0.free.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Obtains an uninitialised slot for a value of the given layout, recycling an idle block of
+    /// the same size class if there is one.
+    #[inline]
+    pub(crate) fn allocate(&self, value: Layout) -> NonNull<u8> {
+        Owner::Pool(self.clone()).allocate(value)
+    }
+
+    fn obtain(&self, block: Layout) -> NonNull<u8> {
+        let recycled = self.free().get_mut(&block).and_then(Vec::pop);
+        match recycled {
+            Some(Block(base)) => base,
+            None => {
+                // SAFETY: the block layout is never zero-sized, as it includes the header
+                let base = unsafe { alloc::alloc(block) };
+                NonNull::new(base).unwrap_or_else(|| alloc::handle_alloc_error(block))
+            }
+        }
+    }
+
+    fn recycle(&self, base: NonNull<u8>, block: Layout) {
+        let mut free = self.free();
+        let blocks = free.entry(block).or_default();
+        if blocks.len() < self.0.limit {
+            blocks.push(Block(base));
+        } else {
+            // SAFETY: the block was allocated with this layout
+            unsafe { alloc::dealloc(base.as_ptr(), block) };
+        }
+    }
+
+    /// Obtains a slot from the same pool or allocator, and for the same layout, as an existing
+    /// slot.
+    ///
+    /// # Safety
+    /// [slot] must have been returned by [`Pool::allocate()`] or `allocate_in()` and not yet
+    /// released.
+    pub(crate) unsafe fn allocate_like(slot: NonNull<u8>) -> NonNull<u8> {
+        let header = unsafe { header(slot).as_ref() };
+        header.owner.allocate(header.layout)
+    }
+
+    /// Returns a slot's block to its pool or allocator.
+    ///
+    /// # Safety
+    /// [slot] must have been returned by [`Pool::allocate()`] or `allocate_in()` and not yet
+    /// released, and its value must have been dropped or moved out.
+    pub(crate) unsafe fn release(slot: NonNull<u8>) {
+        let header = unsafe { header(slot).read() };
+        let (block, offset) = block_layout(header.layout);
+        let base = unsafe { slot.sub(offset) };
+        match header.owner {
+            Owner::Pool(pool) => pool.recycle(base, block),
+            // SAFETY: the block was allocated with this layout
+            #[cfg(feature = "allocator_api")]
+            Owner::Allocator(allocator) => unsafe { allocator.deallocate(base, block) },
+        }
+    }
+}
+
+/// Obtains an uninitialised slot for a value of the given layout from a caller-provided
+/// allocator.
+#[cfg(feature = "allocator_api")]
+#[inline]
+pub(crate) fn allocate_in(allocator: Arc<dyn Allocator>, value: Layout) -> NonNull<u8> {
+    Owner::Allocator(allocator).allocate(value)
+}
+
+impl Owner {
+    fn allocate(&self, value: Layout) -> NonNull<u8> {
+        let (block, offset) = block_layout(value);
+        let base = match self {
+            Owner::Pool(pool) => pool.obtain(block),
+            #[cfg(feature = "allocator_api")]
+            Owner::Allocator(allocator) => allocator
+                .allocate(block)
+                .unwrap_or_else(|| alloc::handle_alloc_error(block)),
+        };
+        // SAFETY: the header ends where the value begins, both within the block
+        unsafe {
+            let slot = base.add(offset);
+            header(slot).write(Header {
+                owner: self.clone(),
+                layout: value,
+            });
+            slot
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Probing a `dyn Every` against a closed set of candidate types in a single call.
+//!
+//! A set of candidates is expressed as a tuple, e.g. `value.downcast_first_of::<(i32, &str)>()`,
+//! yielding a [OneOf2] (and so forth) that identifies which candidate matched.
+
+use crate::every::{cannot_downcast, DowncastError, Every};
+use std::any::TypeId;
+
+/// A tuple of candidate types.
+pub trait TypeSet: 'static {
+    /// A reference to a value of one of the candidate types.
+    type Ref<'a>;
+
+    /// Whether any of the candidate types has the given [TypeId].
+    fn contains(type_id: TypeId) -> bool;
+
+    /// Downcasts to the first candidate type that matches.
+    fn downcast_first(value: &dyn Every) -> Option<Self::Ref<'_>>;
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OneOf2<'a, A, B> {
+    First(&'a A),
+    Second(&'a B),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OneOf3<'a, A, B, C> {
+    First(&'a A),
+    Second(&'a B),
+    Third(&'a C),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OneOf4<'a, A, B, C, D> {
+    First(&'a A),
+    Second(&'a B),
+    Third(&'a C),
+    Fourth(&'a D),
+}
+
+impl<A: 'static> TypeSet for (A,) {
+    type Ref<'a> = &'a A;
+
+    #[inline]
+    fn contains(type_id: TypeId) -> bool {
+        type_id == TypeId::of::<A>()
+    }
+
+    #[inline]
+    fn downcast_first(value: &dyn Every) -> Option<&A> {
+        value.downcast_ref().ok()
+    }
+}
+
+macro_rules! impl_type_set {
+    ($one_of:ident; $($ty:ident => $variant:ident),+) => {
+        impl<$($ty: 'static),+> TypeSet for ($($ty,)+) {
+            type Ref<'a> = $one_of<'a, $($ty),+>;
+
+            #[inline]
+            fn contains(type_id: TypeId) -> bool {
+                $(type_id == TypeId::of::<$ty>())||+
+            }
+
+            #[inline]
+            fn downcast_first(value: &dyn Every) -> Option<Self::Ref<'_>> {
+                $(
+                    if value.is::<$ty>() {
+                        // SAFETY: just checked the type
+                        return Some($one_of::$variant(unsafe { value.downcast_ref_unchecked() }));
+                    }
+                )+
+                None
+            }
+        }
+    };
+}
+
+impl_type_set!(OneOf2; A => First, B => Second);
+impl_type_set!(OneOf3; A => First, B => Second, C => Third);
+impl_type_set!(OneOf4; A => First, B => Second, C => Third, D => Fourth);
+
+impl dyn Every {
+    /// Whether the value is of any of the types in the tuple [S].
+    #[inline]
+    pub fn is_any_of<S: TypeSet>(&self) -> bool {
+        S::contains(self.type_id())
+    }
+
+    /// Downcasts the value to the first matching type in the tuple [S].
+    #[inline]
+    pub fn downcast_first_of<S: TypeSet>(&self) -> Result<S::Ref<'_>, DowncastError> {
+        S::downcast_first(self).ok_or_else(|| cannot_downcast::<S>(self))
+    }
+}
+
+impl dyn Every + Send {
+    #[inline]
+    pub fn is_any_of<S: TypeSet>(&self) -> bool {
+        <dyn Every>::is_any_of::<S>(self)
+    }
+
+    #[inline]
+    pub fn downcast_first_of<S: TypeSet>(&self) -> Result<S::Ref<'_>, DowncastError> {
+        <dyn Every>::downcast_first_of::<S>(self)
+    }
+}
+
+impl dyn Every + Send + Sync {
+    #[inline]
+    pub fn is_any_of<S: TypeSet>(&self) -> bool {
+        <dyn Every>::is_any_of::<S>(self)
+    }
+
+    #[inline]
+    pub fn downcast_first_of<S: TypeSet>(&self) -> Result<S::Ref<'_>, DowncastError> {
+        <dyn Every>::downcast_first_of::<S>(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::every::Every;
+    use crate::probe::{OneOf2, OneOf3, OneOf4};
+    use std::any::TypeId;
+
+    #[test]
+    fn is_any_of() {
+        let val = Box::new(42i32) as Box<dyn Every>;
+        assert!(val.is_any_of::<(i32,)>());
+        assert!(val.is_any_of::<(&str, i32)>());
+        assert!(!val.is_any_of::<(&str, u32, f64)>());
+    }
+
+    #[test]
+    fn downcast_first_of() {
+        let val = Box::new(42i32) as Box<dyn Every>;
+        assert_eq!(Ok(&42), val.downcast_first_of::<(i32,)>());
+        assert_eq!(Ok(OneOf2::Second(&42)), val.downcast_first_of::<(&str, i32)>());
+        assert_eq!(Ok(OneOf3::First(&42)), val.downcast_first_of::<(i32, &str, i64)>());
+        assert_eq!(Ok(OneOf4::Fourth(&42)), val.downcast_first_of::<(u8, u16, u32, i32)>());
+    }
+
+    #[test]
+    fn downcast_first_of_error() {
+        let val = Box::new(42i32) as Box<dyn Every + Send + Sync>;
+        let err = val.downcast_first_of::<(u8, &str)>().unwrap_err();
+        assert_eq!(TypeId::of::<(u8, &str)>(), err.target_type_id);
+        assert_eq!("(u8, &str)", err.target_type_name);
+    }
+}
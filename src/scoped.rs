@@ -0,0 +1,125 @@
+//! Type erasure for values containing non-`'static` borrows.
+//!
+//! [Every] requires `'static`, ruling out payloads that borrow. An [EveryRef] erases a reference to
+//! any [Transient] type, identifying it by the [TypeId] of its `'static` twin. [EveryRef] is
+//! invariant in its lifetime, so a downcast can never shorten the borrows inside the value.
+//!
+//! [Every]: crate::every::Every
+
+use crate::every::DowncastError;
+use std::any;
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+/// Maps a type that may borrow for `'a` to its `'static` counterpart.
+///
+/// # Safety
+/// `Static` must be `Self` with every occurrence of `'a` replaced by `'static`, and `'a` must be
+/// the only lifetime parameter of `Self`.
+pub unsafe trait Transient<'a>: 'a {
+    type Static: 'static;
+}
+
+unsafe impl<'a> Transient<'a> for &'a str {
+    type Static = &'static str;
+}
+
+unsafe impl<'a, T: 'static> Transient<'a> for &'a [T] {
+    type Static = &'static [T];
+}
+
+unsafe impl<'a> Transient<'a> for String {
+    type Static = String;
+}
+
+/// A type-erased reference to a [Transient] value, valid for `'a`.
+#[derive(Clone, Copy)]
+pub struct EveryRef<'a> {
+    ptr: *const (),
+    type_id: TypeId,
+    type_name: &'static str,
+    _invariant: PhantomData<fn(&'a ()) -> &'a ()>,
+}
+
+impl<'a> EveryRef<'a> {
+    #[inline]
+    pub fn new<T: Transient<'a>>(value: &'a T) -> Self {
+        Self {
+            ptr: value as *const T as *const (),
+            type_id: TypeId::of::<T::Static>(),
+            type_name: any::type_name::<T>(),
+            _invariant: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    #[inline]
+    pub fn is<T: Transient<'a>>(&self) -> bool {
+        TypeId::of::<T::Static>() == self.type_id
+    }
+
+    #[inline]
+    pub fn downcast_ref<T: Transient<'a>>(&self) -> Result<&'a T, DowncastError> {
+        if self.is::<T>() {
+            // SAFETY: the pointer was created from a `&'a T` with the same `'static` twin, and
+            // invariance of `'a` guarantees the borrows inside `T` are unchanged
+            Ok(unsafe { &*(self.ptr as *const T) })
+        } else {
+            Err(DowncastError {
+                source_type_id: self.type_id,
+                source_type_name: self.type_name.into(),
+                target_type_id: TypeId::of::<T::Static>(),
+                target_type_name: any::type_name::<T>().into(),
+                source_debug: None,
+                suggestion: None,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scoped::{EveryRef, Transient};
+    use std::any::TypeId;
+
+    #[derive(Debug, PartialEq)]
+    struct Borrowing<'a> {
+        name: &'a str,
+    }
+
+    unsafe impl<'a> Transient<'a> for Borrowing<'a> {
+        type Static = Borrowing<'static>;
+    }
+
+    #[test]
+    fn downcast_ref_ok() {
+        let name = String::from("foo");
+        let value = Borrowing { name: &name };
+        let erased = EveryRef::new(&value);
+        assert!(erased.is::<Borrowing>());
+        assert_eq!(Ok(&Borrowing { name: "foo" }), erased.downcast_ref());
+    }
+
+    #[test]
+    fn downcast_ref_error() {
+        let name = String::from("foo");
+        let value = name.as_str();
+        let erased = EveryRef::new(&value);
+        let err = erased.downcast_ref::<Borrowing>().unwrap_err();
+        assert_eq!(TypeId::of::<&'static str>(), err.source_type_id);
+        assert_eq!("cannot downcast &str into Borrowing<'_>", format!("{err:#}"));
+    }
+
+    #[test]
+    fn downcast_slice() {
+        let values = vec![1u8, 2, 3];
+        let slice = values.as_slice();
+        let erased = EveryRef::new(&slice);
+        assert_eq!(&[1u8, 2, 3], *erased.downcast_ref::<&[u8]>().unwrap());
+        assert_eq!("&[u8]", erased.type_name());
+    }
+}
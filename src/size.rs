@@ -0,0 +1,114 @@
+//! Reporting the memory held by values, including that owned on the heap.
+
+use std::collections::{BTreeMap, HashMap};
+use std::mem;
+
+/// Reports the heap memory owned by a value, over and above its inline [`mem::size_of()`].
+pub trait DeepSize {
+    fn deep_size_of_children(&self) -> usize;
+
+    /// The total memory attributable to the value.
+    fn deep_size_of(&self) -> usize
+    where
+        Self: Sized,
+    {
+        mem::size_of::<Self>() + self.deep_size_of_children()
+    }
+}
+
+macro_rules! impl_flat {
+    ($($ty:ty),+) => {
+        $(
+            impl DeepSize for $ty {
+                #[inline]
+                fn deep_size_of_children(&self) -> usize {
+                    0
+                }
+            }
+        )+
+    };
+}
+
+impl_flat!(
+    (), bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64,
+    &'static str
+);
+
+impl DeepSize for String {
+    fn deep_size_of_children(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: DeepSize> DeepSize for Box<T> {
+    fn deep_size_of_children(&self) -> usize {
+        (**self).deep_size_of()
+    }
+}
+
+impl<T: DeepSize> DeepSize for Option<T> {
+    fn deep_size_of_children(&self) -> usize {
+        self.as_ref().map_or(0, T::deep_size_of_children)
+    }
+}
+
+impl<T: DeepSize, const N: usize> DeepSize for [T; N] {
+    fn deep_size_of_children(&self) -> usize {
+        self.iter().map(T::deep_size_of_children).sum()
+    }
+}
+
+impl<T: DeepSize> DeepSize for Vec<T> {
+    fn deep_size_of_children(&self) -> usize {
+        let elements: usize = self.iter().map(T::deep_size_of_children).sum();
+        self.capacity() * mem::size_of::<T>() + elements
+    }
+}
+
+/// Approximates the table by its entries, excluding control bytes and spare capacity.
+impl<K: DeepSize, V: DeepSize, S> DeepSize for HashMap<K, V, S> {
+    fn deep_size_of_children(&self) -> usize {
+        let entries: usize = self
+            .iter()
+            .map(|(key, value)| key.deep_size_of_children() + value.deep_size_of_children())
+            .sum();
+        self.capacity() * mem::size_of::<(K, V)>() + entries
+    }
+}
+
+/// Approximates the tree by its entries, excluding node overheads.
+impl<K: DeepSize, V: DeepSize> DeepSize for BTreeMap<K, V> {
+    fn deep_size_of_children(&self) -> usize {
+        self.iter()
+            .map(|(key, value)| key.deep_size_of() + value.deep_size_of())
+            .sum()
+    }
+}
+
+impl<A: DeepSize, B: DeepSize> DeepSize for (A, B) {
+    fn deep_size_of_children(&self) -> usize {
+        self.0.deep_size_of_children() + self.1.deep_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::size::DeepSize;
+    use std::mem;
+
+    #[test]
+    fn flat() {
+        assert_eq!(4, 42u32.deep_size_of());
+        assert_eq!(0, "foo".deep_size_of_children());
+    }
+
+    #[test]
+    fn nested() {
+        let mut vec = Vec::with_capacity(4);
+        vec.push(String::with_capacity(10));
+        vec.push(String::new());
+        let expected = mem::size_of::<Vec<String>>() + 4 * mem::size_of::<String>() + 10;
+        assert_eq!(expected, vec.deep_size_of());
+        assert_eq!(2, Some(Box::new(0u16)).deep_size_of_children());
+    }
+}
@@ -0,0 +1,133 @@
+//! Type identities that remain stable across compilation units.
+//!
+//! A [TypeId] is only meaningful within the binary that computed it, which rules it out as a
+//! means of identifying erased values that cross a dynamic-library boundary or are exchanged with
+//! separately compiled workers. A [StableTypeId] is instead derived from a content hash of the
+//! type name or, where the type implements [TypeTag] and has been registered with [register],
+//! of a user-provided tag.
+
+use crate::every::Every;
+use std::any;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
+/// The FNV-1a offset basis.
+pub(crate) const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Folds `bytes` into an FNV-1a `hash`, which is stable across compiler versions and platforms.
+pub(crate) const fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        i += 1;
+    }
+    hash
+}
+
+/// A type identity derived from a type name or a user-provided tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StableTypeId(pub u64);
+
+impl StableTypeId {
+    /// The stable identity of [T]: that of its registered tag, if any, otherwise that of its
+    /// type name.
+    pub fn of<T: ?Sized + 'static>() -> Self {
+        Registry::singleton()
+            .get(TypeId::of::<T>())
+            .unwrap_or_else(|| Self::from_name(any::type_name::<T>()))
+    }
+
+    /// The stable identity of a tag or type name.
+    pub const fn from_name(name: &str) -> Self {
+        Self(fnv1a(FNV_OFFSET, name.as_bytes()))
+    }
+}
+
+/// A user-provided tag identifying a type irrespective of its path and of the compiler, which
+/// determine its [type name](any::type_name). Tags only take effect once [registered](register).
+pub trait TypeTag: 'static {
+    const TAG: &'static str;
+}
+
+#[derive(Default)]
+struct Registry {
+    tags: RwLock<HashMap<TypeId, StableTypeId>>,
+}
+
+impl Registry {
+    fn singleton() -> &'static Registry {
+        static LAZY: LazyLock<Registry> = LazyLock::new(Default::default);
+        &LAZY
+    }
+
+    fn get(&self, type_id: TypeId) -> Option<StableTypeId> {
+        self.tags.read().unwrap().get(&type_id).copied()
+    }
+}
+
+/// Registers the [TypeTag] of [T], which thereafter determines its [StableTypeId].
+pub fn register<T: TypeTag>() {
+    let stable_type_id = StableTypeId::from_name(T::TAG);
+    let mut tags = Registry::singleton().tags.write().unwrap();
+    tags.insert(TypeId::of::<T>(), stable_type_id);
+}
+
+impl dyn Every {
+    /// The [StableTypeId] of the value's concrete type.
+    pub fn stable_type_id(&self) -> StableTypeId {
+        Registry::singleton()
+            .get(self.type_id())
+            .unwrap_or_else(|| StableTypeId::from_name(self.type_name()))
+    }
+}
+
+impl dyn Every + Send {
+    #[inline]
+    pub fn stable_type_id(&self) -> StableTypeId {
+        <dyn Every>::stable_type_id(self)
+    }
+}
+
+impl dyn Every + Send + Sync {
+    #[inline]
+    pub fn stable_type_id(&self) -> StableTypeId {
+        <dyn Every>::stable_type_id(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::every::Every;
+    use crate::stable::{register, StableTypeId, TypeTag};
+
+    #[test]
+    fn from_type_name() {
+        let val = Box::new(42u32) as Box<dyn Every>;
+        assert_eq!(StableTypeId::from_name("u32"), val.stable_type_id());
+        assert_eq!(StableTypeId::of::<u32>(), val.stable_type_id());
+        assert_ne!(StableTypeId::of::<i32>(), val.stable_type_id());
+
+        // FNV-1a of "u32"
+        assert_eq!(StableTypeId(0x4d2b_df19_3e85_26d1), StableTypeId::of::<u32>());
+    }
+
+    #[test]
+    fn from_tag() {
+        struct Order;
+
+        impl TypeTag for Order {
+            const TAG: &'static str = "com.example.Order";
+        }
+
+        const ORDER: StableTypeId = StableTypeId::from_name(Order::TAG);
+        assert_ne!(ORDER, StableTypeId::of::<Order>());
+
+        register::<Order>();
+        assert_eq!(ORDER, StableTypeId::of::<Order>());
+        let val = Box::new(Order) as Box<dyn Every + Send + Sync>;
+        assert_eq!(ORDER, val.stable_type_id());
+    }
+}
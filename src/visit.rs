@@ -0,0 +1,128 @@
+//! Visitor-style double dispatch over `dyn Every`.
+//!
+//! A visitor implements [Visit] for each concrete type it handles; each `(visitor, value)` pair is
+//! then registered with [register]. Thereafter, [`<dyn Every>::accept`] dispatches to the matching
+//! [Visit] implementation, falling back to [EveryVisitor::visit_unknown] for unregistered pairs.
+
+use crate::every::Every;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
+/// A visitor of heterogeneous `dyn Every` values.
+pub trait EveryVisitor: Every {
+    /// Invoked for values whose type has no registered [Visit] implementation for this visitor.
+    #[allow(unused_variables)]
+    fn visit_unknown(&mut self, value: &dyn Every) {}
+}
+
+/// Visits values of a specific type [T].
+pub trait Visit<T> {
+    fn visit(&mut self, value: &T);
+}
+
+type VisitFn = fn(&mut dyn EveryVisitor, &dyn Every);
+
+fn visit<V: Visit<T> + 'static, T: 'static>(visitor: &mut dyn EveryVisitor, value: &dyn Every) {
+    // SAFETY: the thunk is only looked up for visitor and value types matching V and T
+    let visitor = unsafe { (visitor as &mut dyn Every).downcast_mut_unchecked::<V>() };
+    let value = unsafe { value.downcast_ref_unchecked::<T>() };
+    visitor.visit(value);
+}
+
+#[derive(Default)]
+struct Registry {
+    thunks: RwLock<HashMap<(TypeId, TypeId), VisitFn>>,
+}
+
+impl Registry {
+    fn singleton() -> &'static Registry {
+        static LAZY: LazyLock<Registry> = LazyLock::new(Default::default);
+        &LAZY
+    }
+}
+
+/// Registers the [Visit] implementation of visitor [V] for values of type [T].
+pub fn register<V: EveryVisitor + Visit<T>, T: 'static>() {
+    let key = (TypeId::of::<V>(), TypeId::of::<T>());
+    let mut thunks = Registry::singleton().thunks.write().unwrap();
+    thunks.insert(key, visit::<V, T>);
+}
+
+impl dyn Every {
+    /// Dispatches this value to the `visitor`.
+    pub fn accept(&self, visitor: &mut dyn EveryVisitor) {
+        let key = ((*visitor).type_id(), self.type_id());
+        let thunk = Registry::singleton().thunks.read().unwrap().get(&key).copied();
+        match thunk {
+            None => visitor.visit_unknown(self),
+            Some(thunk) => thunk(visitor, self),
+        }
+    }
+}
+
+impl dyn Every + Send {
+    #[inline]
+    pub fn accept(&self, visitor: &mut dyn EveryVisitor) {
+        <dyn Every>::accept(self, visitor)
+    }
+}
+
+impl dyn Every + Send + Sync {
+    #[inline]
+    pub fn accept(&self, visitor: &mut dyn EveryVisitor) {
+        <dyn Every>::accept(self, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::every::Every;
+    use crate::visit::{register, EveryVisitor, Visit};
+
+    #[derive(Default)]
+    struct Summary {
+        sum: i64,
+        text: String,
+        unknown: Vec<&'static str>,
+    }
+
+    impl EveryVisitor for Summary {
+        fn visit_unknown(&mut self, value: &dyn Every) {
+            self.unknown.push(value.type_name());
+        }
+    }
+
+    impl Visit<i32> for Summary {
+        fn visit(&mut self, value: &i32) {
+            self.sum += *value as i64;
+        }
+    }
+
+    impl Visit<&'static str> for Summary {
+        fn visit(&mut self, value: &&'static str) {
+            self.text.push_str(value);
+        }
+    }
+
+    #[test]
+    fn accept() {
+        register::<Summary, i32>();
+        register::<Summary, &'static str>();
+
+        let values: Vec<Box<dyn Every>> = vec![
+            Box::new(40i32),
+            Box::new("foo"),
+            Box::new(2i32),
+            Box::new(3.5f64),
+            Box::new("bar"),
+        ];
+        let mut summary = Summary::default();
+        for value in &values {
+            value.accept(&mut summary);
+        }
+        assert_eq!(42, summary.sum);
+        assert_eq!("foobar", summary.text);
+        assert_eq!(vec!["f64"], summary.unknown);
+    }
+}
@@ -1,57 +1,847 @@
+use crate::every::Every;
+use crate::stable::{fnv1a, FNV_OFFSET};
+use crate::DebugFn;
+use std::any;
 use std::any::{Any, TypeId};
+use std::cell::RefCell;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasherDefault, DefaultHasher, Hash, Hasher};
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
 use std::marker::PhantomData;
-use std::sync::{LazyLock, RwLock};
+use std::panic;
+use std::panic::{AssertUnwindSafe, RefUnwindSafe, UnwindSafe};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, OnceLock, Weak};
+#[cfg(not(feature = "parking_lot"))]
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+#[cfg(feature = "parking_lot")]
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, Instant, SystemTime};
 
 /// Specialises a vtable for [T].
+///
+/// With the `derive` feature enabled, `#[derive(Specialise)]` implements the trait for structs
+/// whose fields are the standard thunks ([CloneFn](crate::CloneFn), [DebugFn],
+/// `PartialEqFn` and [HashFn](crate::HashFn)), selected by each field's type or name.
 pub trait Specialise<T> {
     fn specialise() -> Self;
+
+    /// The [Debug](std::fmt::Debug) thunk for [T], if the vtable carries one. When present, it is
+    /// used to render the offending value in a [DowncastError](crate::every::DowncastError).
+    fn debug_fn() -> Option<DebugFn> {
+        None
+    }
+
+    /// Specialises the vtable in light of the `context` of the [Registry] creating it, as given
+    /// to [`Registry::with_context()`]; `()` is supplied by registries lacking a context.
+    /// Implementations may downcast the context to a configuration type of their choosing,
+    /// falling back to [`Specialise::specialise()`] (the default) for unrecognised contexts.
+    #[allow(unused_variables)]
+    fn specialise_with(context: &dyn Any) -> Self
+    where
+        Self: Sized,
+    {
+        Self::specialise()
+    }
+}
+
+/// Source of [Record] sequence numbers, shared among all registries.
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(feature = "derive")]
+pub use vtable_derive::Specialise;
+
+#[cfg(feature = "testing")]
+thread_local! {
+    static ISOLATED: std::cell::Cell<Option<&'static Registry>> =
+        const { std::cell::Cell::new(None) };
+}
+
+/// Restores the previously effective registry when dropped. See [`Registry::isolate()`].
+#[cfg(feature = "testing")]
+pub struct IsolationGuard {
+    previous: Option<&'static Registry>,
+    _not_send: PhantomData<*const ()>,
+}
+
+#[cfg(feature = "testing")]
+impl Drop for IsolationGuard {
+    fn drop(&mut self) {
+        ISOLATED.set(self.previous);
+    }
+}
+
+/// Composes two vtables specialised for the same [T]. Unlike a composite token obtained with
+/// [`Token::join()`], the composed vtables are owned, and specialised afresh.
+impl<T, A: Specialise<T>, B: Specialise<T>> Specialise<T> for (A, B) {
+    fn specialise() -> Self {
+        (A::specialise(), B::specialise())
+    }
+
+    fn debug_fn() -> Option<DebugFn> {
+        A::debug_fn().or_else(B::debug_fn)
+    }
+
+    fn specialise_with(context: &dyn Any) -> Self {
+        (A::specialise_with(context), B::specialise_with(context))
+    }
 }
 
+/// The number of independently locked shards in the [Registry]. Must be a power of two.
+const SHARDS: usize = 16;
+
+/// A registry of specialised vtables. A process-wide singleton backs [`Token::default()`];
+/// independent instances may be created with [`Registry::new()`] and populated through
+/// [`Token::in_registry()`], keeping their entries isolated from other registries. Vtables are
+/// leaked regardless of the registry that created them, as tokens refer to them statically.
+///
+/// Entries are distributed among [SHARDS] shards by the [TypeId] of the value type, so that all
+/// entries pertaining to one value type reside in the same shard. Concurrent registrations of
+/// different value types thereby rarely contend on the same lock.
 #[derive(Default)]
-struct Registry {
-    internals: RwLock<RegistryInternals>,
+pub struct Registry {
+    shards: [RwLock<RegistryInternals>; SHARDS],
+
+    /// Indexes vtables by the name of their value type and the [TypeId] of their vtable type.
+    names: RwLock<HashMap<(&'static str, TypeId), &'static (dyn Any + Sync + Send)>>,
+
+    /// Vtables by [VTableId], densely allocated in the order of registration.
+    ids: RwLock<Vec<&'static (dyn Any + Sync + Send)>>,
+    context: Option<Box<dyn Any + Sync + Send>>,
+    metrics: OnceLock<Box<dyn RegistryMetrics>>,
+    observers: RwLock<Vec<Box<Observer>>>,
+
+    /// Advanced whenever an entry is overridden, invalidating tokens cached by [global_token].
+    generation: AtomicU64,
+}
+
+type Observer = dyn Fn(&'static str, &'static str) + Sync + Send;
+
+/// Receives measurements of a [Registry], once installed with [`Registry::install_metrics()`].
+/// All methods do nothing by default. They are invoked with no registry locks held, so that they
+/// may inspect the registry.
+#[allow(unused_variables)]
+pub trait RegistryMetrics: Sync + Send {
+    /// A token was acquired for a `(T, V)` pair that was already registered. Tokens served by the
+    /// thread-local cache of [global_token], or by a [static_token](crate::static_token) call
+    /// site, bypass the registry and are not reported.
+    fn hit(&self) {}
+
+    /// A token was requested for a `(T, V)` pair that was not yet registered.
+    fn miss(&self) {}
+
+    /// A vtable was specialised for a new `(T, V)` pair.
+    fn specialised(&self, type_name: &'static str, vtable_type_name: &'static str) {}
+
+    /// A shard lock was acquired after waiting for the given duration.
+    fn lock_wait(&self, wait: Duration) {}
+}
+
+/// A [RegistryMetrics] implementation that accumulates measurements in atomic counters.
+#[derive(Debug, Default)]
+pub struct Counters {
+    pub hits: AtomicU64,
+    pub misses: AtomicU64,
+    pub specialisations: AtomicU64,
+
+    /// The total time spent waiting for shard locks, in nanoseconds.
+    pub lock_wait_nanos: AtomicU64,
+}
+
+impl RegistryMetrics for Counters {
+    fn hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn specialised(&self, _type_name: &'static str, _vtable_type_name: &'static str) {
+        self.specialisations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn lock_wait(&self, wait: Duration) {
+        let nanos = u64::try_from(wait.as_nanos()).unwrap_or(u64::MAX);
+        self.lock_wait_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+impl RegistryMetrics for Arc<Counters> {
+    fn hit(&self) {
+        (**self).hit()
+    }
+
+    fn miss(&self) {
+        (**self).miss()
+    }
+
+    fn specialised(&self, type_name: &'static str, vtable_type_name: &'static str) {
+        (**self).specialised(type_name, vtable_type_name)
+    }
+
+    fn lock_wait(&self, wait: Duration) {
+        (**self).lock_wait(wait)
+    }
 }
 
 #[derive(Default)]
 struct RegistryInternals {
     types: HashMap<(TypeId, TypeId), Record>,
+    debug_fns: HashMap<TypeId, DebugFn>,
+    redacted: HashSet<TypeId>,
+
+    /// Conversions into [Every] of the value types for which a vtable exists. See
+    /// [`every::from_any()`](crate::every::from_any).
+    from_any_fns: HashMap<TypeId, FromAnyFn>,
+}
+
+type FromAnyFn = fn(Box<dyn Any>) -> Box<dyn Every>;
+
+fn from_any<T: 'static>(value: Box<dyn Any>) -> Box<dyn Every> {
+    // the conversion is only looked up for values whose type ID matches T
+    value.downcast::<T>().expect("keyed by type")
+}
+
+impl RegistryInternals {
+    /// Records the [Debug](std::fmt::Debug) thunk for a type, unless the type is redacted.
+    fn debug_fn(&mut self, type_id: TypeId, debug_fn: DebugFn) {
+        if !self.redacted.contains(&type_id) {
+            self.debug_fns.insert(type_id, debug_fn);
+        }
+    }
+}
+
+struct Record {
+    /// A leaked `Token<T, V>`, whose address remains stable for the life of the process.
+    token: &'static (dyn Any + Sync + Send),
+
+    /// The leaked `V` to which the token refers.
+    vtable: &'static (dyn Any + Sync + Send),
+    type_name: &'static str,
+    vtable_type_name: &'static str,
+    registered_at: SystemTime,
+
+    /// Orders entries by registration, as [SystemTime] may not discriminate between them.
+    sequence: u64,
+
+    /// The number of bytes leaked to create the entry.
+    leaked_bytes: usize,
+
+    /// Assigned by the registry upon insertion.
+    id: VTableId,
+}
+
+/// A registration passed to [`Registry::preload()`] panicked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreloadError {
+    /// The position of the failed registration.
+    pub index: usize,
+
+    /// The panic message, if it was a string.
+    pub message: Option<String>,
+}
+
+impl Display for PreloadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "registration {} panicked", self.index)?;
+        match &self.message {
+            None => Ok(()),
+            Some(message) => write!(f, ": {message}"),
+        }
+    }
+}
+
+impl Error for PreloadError {}
+
+/// A compact identifier of a vtable within a [Registry]. See [`Registry::id()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VTableId(pub u32);
+
+/// Memory accounting for a [Registry], as reported by [`Registry::stats()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RegistryStats {
+    /// The number of registered `(T, V)` entries.
+    pub entries: usize,
+
+    /// The approximate number of bytes permanently leaked by the registered vtables and tokens.
+    pub leaked_bytes: usize,
+}
+
+impl Record {
+    /// Leaks the `vtable` along with a token referring to it.
+    fn leak<T: 'static, V: Sync + Send + 'static>(vtable: V) -> Self {
+        let vtable: &'static V = Box::leak(Box::new(vtable));
+        let token: &'static Token<T, V> = Box::leak(Box::new(Token::create_unchecked(vtable)));
+        Self {
+            token,
+            vtable,
+            type_name: any::type_name::<T>(),
+            vtable_type_name: any::type_name::<V>(),
+            registered_at: SystemTime::now(),
+            sequence: SEQUENCE.fetch_add(1, Ordering::Relaxed),
+            leaked_bytes: size_of::<V>() + size_of::<Token<T, V>>(),
+            id: VTableId(u32::MAX),
+        }
+    }
+}
+
+impl Debug for Registry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Registry")
+            .field("entries", &self.entries())
+            .field("context", &self.context.is_some())
+            .field("metrics", &self.metrics.get().is_some())
+            .finish()
+    }
+}
+
+impl Debug for RegistryInternals {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.types.values().map(|record| (record.type_name, record.vtable_type_name)))
+            .finish()
+    }
+}
+
+/// A value type for which a [V]-type vtable has been specialised, as enumerated by
+/// [`Registry::types_for()`]. The vtable stands in for a token, the value type being erased.
+#[derive(Debug)]
+pub struct Specialised<V: 'static> {
+    pub type_id: TypeId,
+    pub type_name: &'static str,
+    pub vtable: &'static V,
 }
 
-struct Record(Box<dyn Any + Sync + Send>);
+/// Describes an entry in a [Registry], as enumerated by [`Registry::entries()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryInfo {
+    /// The name of the value type [T] for which the vtable was specialised.
+    pub type_name: &'static str,
+
+    /// The name of the vtable type [V].
+    pub vtable_type_name: &'static str,
+
+    /// When the entry was added to the registry.
+    pub registered_at: SystemTime,
+}
 
 impl Registry {
-    fn singleton() -> &'static Registry {
-        static LAZY: LazyLock<Registry> = LazyLock::new(Default::default);
+    /// Creates an empty registry, independent of the singleton.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty registry whose vtables are specialised with the given `context`, passed to
+    /// [`Specialise::specialise_with()`].
+    pub fn with_context<C: Sync + Send + 'static>(context: C) -> Self {
+        Self {
+            context: Some(Box::new(context)),
+            ..Self::default()
+        }
+    }
+
+    /// The process-wide registry backing [`Token::default()`]. With the `testing` feature
+    /// enabled, this is the isolated registry of the current thread, if one has been installed
+    /// with [`Registry::isolate()`].
+    pub fn singleton() -> &'static Registry {
+        #[cfg(feature = "testing")]
+        if let Some(registry) = ISOLATED.get() {
+            return registry;
+        }
+
+        static LAZY: LazyLock<Registry> = LazyLock::new(|| {
+            let registry = Registry::default();
+            #[cfg(feature = "inventory")]
+            registry.collect_distributed();
+            registry
+        });
         &LAZY
     }
 
-    fn get_or_create<T: 'static, V: Specialise<T> + Sync + Send + 'static>(&self) -> &'static V {
-        let mut internals = self.internals.write().unwrap();
+    /// Installs a fresh registry in place of the singleton for the current thread, so that a test
+    /// neither observes nor leaks registrations shared with other tests in the same process.
+    /// The previously effective registry is restored when the returned guard is dropped. Each
+    /// isolated registry is leaked, as tokens refer to its vtables statically. Neither the cache of
+    /// [global_token] nor [static_token](crate::static_token) call sites are consulted while
+    /// isolated.
+    #[cfg(feature = "testing")]
+    pub fn isolate() -> IsolationGuard {
+        let registry: &'static Registry = Box::leak(Box::default());
+        IsolationGuard {
+            previous: ISOLATED.replace(Some(registry)),
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Specialises every vtable declared with [register_vtable](crate::register_vtable). The
+    /// singleton does so upon initialisation; instance registries must be populated explicitly.
+    #[cfg(feature = "inventory")]
+    pub fn collect_distributed(&self) {
+        for registration in inventory::iter::<Registration> {
+            (registration.0)(self);
+        }
+    }
+
+    fn shard(&self, type_id: TypeId) -> &RwLock<RegistryInternals> {
+        let mut hasher = DefaultHasher::new();
+        type_id.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize & (SHARDS - 1)]
+    }
+
+    /// Installs the `metrics` to be informed of the registry's activity, returning them back if
+    /// metrics have already been installed.
+    pub fn install_metrics(
+        &self,
+        metrics: impl RegistryMetrics + 'static,
+    ) -> Result<(), Box<dyn RegistryMetrics>> {
+        self.metrics.set(Box::new(metrics))
+    }
+
+    /// Acquires a lock, measuring the wait if metrics are installed. The wait is reported with
+    /// [`Registry::report_wait()`] once the lock has been released, so that the metrics may in
+    /// turn use the registry.
+    fn timed<G>(&self, acquire: impl FnOnce() -> G) -> (G, Option<Duration>) {
+        match self.metrics.get() {
+            None => (acquire(), None),
+            Some(_) => {
+                let start = Instant::now();
+                let guard = acquire();
+                (guard, Some(start.elapsed()))
+            }
+        }
+    }
+
+    fn report_wait(&self, wait: Option<Duration>) {
+        if let Some(metrics) = self.metrics.get()
+            && let Some(wait) = wait
+        {
+            metrics.lock_wait(wait);
+        }
+    }
+
+    fn get_or_create<T: 'static, V: Specialise<T> + Sync + Send + 'static>(
+        &self,
+    ) -> &'static Token<T, V> {
+        if let Some(token) = self.get::<T, V>() {
+            if let Some(metrics) = self.metrics.get() {
+                metrics.hit();
+            }
+            return token;
+        }
+        if let Some(metrics) = self.metrics.get() {
+            metrics.miss();
+        }
+
+        // the entry may have been created by another thread between releasing the read lock and
+        // acquiring the write lock, hence the entry is checked again
+        let (mut guard, wait) = self.timed(|| write(self.shard(TypeId::of::<T>())));
+        let internals = &mut *guard;
         let key = (TypeId::of::<T>(), TypeId::of::<V>());
         let entry = internals.types.entry(key);
-        match entry {
+        let (token, specialised) = match entry {
             Entry::Occupied(entry) => {
                 let record = entry.get();
-                let vtable = record.0.downcast_ref::<&'static V>().unwrap();
-                vtable
+                (record.token.downcast_ref::<Token<T, V>>().unwrap(), false)
             }
             Entry::Vacant(entry) => {
-                let vtable = Box::new(V::specialise());
-                let vtable: &'static V = Box::leak(vtable);
-                entry.insert(Record(Box::new(vtable)));
-                vtable
+                let context = self.context.as_deref().unwrap_or(&());
+                let mut record = Record::leak::<T, V>(V::specialise_with(context));
+                let token = record.token.downcast_ref::<Token<T, V>>().unwrap();
+                self.index::<V>(&mut record, None);
+                entry.insert(record);
+                internals.from_any_fns.insert(TypeId::of::<T>(), from_any::<T>);
+                if let Some(debug_fn) = V::debug_fn() {
+                    internals.debug_fn(TypeId::of::<T>(), debug_fn);
+                }
+                (token, true)
+            }
+        };
+
+        // metrics and observers are notified once the lock is released, so that they may use the
+        // registry
+        drop(guard);
+        self.report_wait(wait);
+        if specialised {
+            if let Some(metrics) = self.metrics.get() {
+                metrics.specialised(any::type_name::<T>(), any::type_name::<V>());
+            }
+            for observer in read(&self.observers).iter() {
+                observer(any::type_name::<T>(), any::type_name::<V>());
+            }
+        }
+        token
+    }
+
+    /// Installs an `observer`, invoked with the names of the value and vtable types whenever a
+    /// vtable is specialised for a new `(T, V)` pair. Observers are invoked on the specialising
+    /// thread, after the registry has been updated.
+    pub fn observe(&self, observer: impl Fn(&'static str, &'static str) + Sync + Send + 'static) {
+        write(&self.observers).push(Box::new(observer));
+    }
+
+    /// Registers a hand-built `vtable` for [T], overriding [`Specialise::specialise()`]. Tokens
+    /// subsequently obtained from this registry refer to the given vtable; those obtained
+    /// previously continue to refer to the vtable they were issued with.
+    pub fn register<T: 'static, V: Specialise<T> + Sync + Send + 'static>(
+        &self,
+        vtable: V,
+    ) -> Token<T, V> {
+        let mut record = Record::leak::<T, V>(vtable);
+        let token = record.token.downcast_ref::<Token<T, V>>().unwrap();
+        let mut internals = write(self.shard(TypeId::of::<T>()));
+        let key = (TypeId::of::<T>(), TypeId::of::<V>());
+        let mut existing_id = None;
+        if let Some(existing) = internals.types.get(&key) {
+            // the replaced vtable remains leaked
+            record.leaked_bytes += existing.leaked_bytes;
+            existing_id = Some(existing.id);
+        }
+        self.index::<V>(&mut record, existing_id);
+        internals.types.insert(key, record);
+        internals.from_any_fns.insert(TypeId::of::<T>(), from_any::<T>);
+        if let Some(debug_fn) = V::debug_fn() {
+            internals.debug_fn(TypeId::of::<T>(), debug_fn);
+        }
+        self.generation.fetch_add(1, Ordering::Release);
+        *token
+    }
+
+    /// Redacts values of type [T] wherever the registry renders them for diagnostics, e.g. in
+    /// [DowncastError](crate::every::DowncastError) snapshots, overriding the [Debug] thunks of
+    /// all vtables specialised for [T], whether previously or subsequently. Vtables must be
+    /// redacted separately, e.g. with [`ched::VTable::redacted()`](crate::ched::VTable::redacted).
+    pub fn redact<T: 'static>(&self) {
+        let mut internals = write(self.shard(TypeId::of::<T>()));
+        internals.redacted.insert(TypeId::of::<T>());
+        internals.debug_fns.insert(TypeId::of::<T>(), crate::redacted);
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+
+    /// Indexes the `record` by name and by identifier, reusing the identifier of the entry that
+    /// it overrides, if any.
+    fn index<V: 'static>(&self, record: &mut Record, existing_id: Option<VTableId>) {
+        let key = (record.type_name, TypeId::of::<V>());
+        write(&self.names).insert(key, record.vtable);
+
+        let mut ids = write(&self.ids);
+        record.id = match existing_id {
+            Some(id) => {
+                ids[id.0 as usize] = record.vtable;
+                id
             }
+            None => {
+                let id = VTableId(u32::try_from(ids.len()).expect("too many vtables"));
+                ids.push(record.vtable);
+                id
+            }
+        };
+    }
+
+    /// The compact identifier of the [T]-specialised [V] vtable, specialising it if one is not
+    /// yet present. Dynamic objects may store the identifier in place of a `&'static V`, for
+    /// instance to shrink their footprint or to represent them on disk or on the wire, resolving
+    /// it with [`Registry::resolve_id()`] when needed, as does [IdCHED](crate::ched::IdCHED).
+    /// Identifiers are allocated densely in the order of registration and are only meaningful to
+    /// the registry that allocated them. An entry overridden with [`Registry::register()`] keeps
+    /// its identifier, which thereafter resolves to the overriding vtable.
+    pub fn id<T: 'static, V: Specialise<T> + Sync + Send + 'static>(&self) -> VTableId {
+        self.get_or_create::<T, V>();
+        let internals = read(self.shard(TypeId::of::<T>()));
+        internals.types[&(TypeId::of::<T>(), TypeId::of::<V>())].id
+    }
+
+    /// Resolves a [VTableId] allocated by [`Registry::id()`], provided it identifies a [V]-type
+    /// vtable.
+    pub fn resolve_id<V: Sync + Send + 'static>(&self, id: VTableId) -> Option<&'static V> {
+        let vtable = *read(&self.ids).get(id.0 as usize)?;
+        vtable.downcast_ref()
+    }
+
+    /// Resolves the [V] vtable specialised for the value type of the given name, as reported by
+    /// [`std::any::type_name()`]. Allows vtables to be looked up by a name read from configuration
+    /// or a wire message. As type names are not guaranteed to be unique or stable across
+    /// compiler versions, names should be produced by the same build that resolves them.
+    pub fn resolve<V: Sync + Send + 'static>(&self, type_name: &str) -> Option<&'static V> {
+        let names = read(&self.names);
+        let vtable = *names.get(&(type_name, TypeId::of::<V>()))?;
+        vtable.downcast_ref()
+    }
+
+    fn get<T: 'static, V: Sync + Send + 'static>(&self) -> Option<&'static Token<T, V>> {
+        let (internals, wait) = self.timed(|| read(self.shard(TypeId::of::<T>())));
+        let key = (TypeId::of::<T>(), TypeId::of::<V>());
+        let token = internals
+            .types
+            .get(&key)
+            .map(|record| record.token.downcast_ref::<Token<T, V>>().unwrap());
+        drop(internals);
+        self.report_wait(wait);
+        token
+    }
+
+    /// Eagerly performs the given registrations, typically during startup, so that the types
+    /// they specialise do not incur latency upon first use. For example:
+    ///
+    /// ```
+    /// use vtable::ched::VTable;
+    /// use vtable::vtable::{Registry, Token};
+    ///
+    /// let registrations: [fn(&Registry); 2] = [
+    ///     |registry| {
+    ///         Token::<i64, VTable>::in_registry(registry);
+    ///     },
+    ///     |registry| {
+    ///         Token::<String, VTable>::in_registry(registry);
+    ///     },
+    /// ];
+    /// Registry::singleton().preload(registrations).unwrap();
+    /// ```
+    ///
+    /// Stops at the first registration that panics, reporting it as a [PreloadError].
+    pub fn preload<F: FnOnce(&Registry)>(
+        &self,
+        registrations: impl IntoIterator<Item = F>,
+    ) -> Result<(), PreloadError> {
+        for (index, registration) in registrations.into_iter().enumerate() {
+            panic::catch_unwind(AssertUnwindSafe(|| registration(self))).map_err(|payload| {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|message| message.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned());
+                PreloadError { index, message }
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Counts the entries in the registry and the bytes leaked on their behalf.
+    pub fn stats(&self) -> RegistryStats {
+        self.shards
+            .iter()
+            .fold(RegistryStats::default(), |mut stats, shard| {
+                let internals = read(shard);
+                stats.entries += internals.types.len();
+                stats.leaked_bytes += internals
+                    .types
+                    .values()
+                    .map(|record| record.leaked_bytes)
+                    .sum::<usize>();
+                stats
+            })
+    }
+
+    /// Enumerates the value types for which a [V]-type vtable has been specialised, in the order
+    /// of registration, e.g. to discover every CHED-capable type.
+    pub fn types_for<V: Sync + Send + 'static>(&self) -> Vec<Specialised<V>> {
+        let mut types = self
+            .shards
+            .iter()
+            .flat_map(|shard| {
+                let internals = read(shard);
+                internals
+                    .types
+                    .iter()
+                    .filter(|((_, vtable_type_id), _)| *vtable_type_id == TypeId::of::<V>())
+                    .map(|(&(type_id, _), record)| {
+                        let specialised = Specialised {
+                            type_id,
+                            type_name: record.type_name,
+                            vtable: record.vtable.downcast_ref().unwrap(),
+                        };
+                        (record.sequence, specialised)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        types.sort_by_key(|&(sequence, _)| sequence);
+        types.into_iter().map(|(_, specialised)| specialised).collect()
+    }
+
+    /// Renders every entry in the registry, one per line in the order of registration, e.g.
+    /// `#0: i32 => vtable::ched::VTable`. Intended for diagnosing unregistered vtables.
+    pub fn dump(&self) -> String {
+        self.entries()
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                format!("#{index}: {} => {}\n", entry.type_name, entry.vtable_type_name)
+            })
+            .collect()
+    }
+
+    /// Enumerates the entries presently in the registry, in the order of registration.
+    pub fn entries(&self) -> Vec<EntryInfo> {
+        let mut entries = self
+            .shards
+            .iter()
+            .flat_map(|shard| {
+                let internals = read(shard);
+                internals
+                    .types
+                    .values()
+                    .map(|record| {
+                        let entry = EntryInfo {
+                            type_name: record.type_name,
+                            vtable_type_name: record.vtable_type_name,
+                            registered_at: record.registered_at,
+                        };
+                        (record.sequence, entry)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|&(sequence, _)| sequence);
+        entries.into_iter().map(|(_, entry)| entry).collect()
+    }
+}
+
+/// The registry remains consistent if a panic unwinds while it is in use. See [read].
+impl UnwindSafe for Registry {}
+
+impl RefUnwindSafe for Registry {}
+
+/// Acquires a read lock, disregarding poisoning. Entries are only inserted once fully
+/// specialised, so a panic while a lock is held (e.g., in [`Specialise::specialise()`]) cannot
+/// leave the guarded map inconsistent. With the `parking_lot` feature enabled, the registry's
+/// locks are drawn from `parking_lot`, which does not poison.
+#[cfg(not(feature = "parking_lot"))]
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Acquires a write lock, disregarding poisoning. See [read].
+#[cfg(not(feature = "parking_lot"))]
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Acquires a read lock if one can be acquired without blocking, disregarding poisoning. See
+/// [read].
+#[cfg(not(feature = "parking_lot"))]
+fn try_read<T>(lock: &RwLock<T>) -> Option<RwLockReadGuard<'_, T>> {
+    match lock.try_read() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::Poisoned(err)) => Some(err.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+#[cfg(feature = "parking_lot")]
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read()
+}
+
+#[cfg(feature = "parking_lot")]
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write()
+}
+
+#[cfg(feature = "parking_lot")]
+fn try_read<T>(lock: &RwLock<T>) -> Option<RwLockReadGuard<'_, T>> {
+    lock.try_read()
+}
+
+/// Renders `value` using a [Debug](std::fmt::Debug) thunk registered for its type, if one exists.
+/// Gives up (rather than blocking) if the registry is locked for writing.
+pub(crate) fn debug_snapshot(value: &dyn Every) -> Option<String> {
+    let debug_fn = {
+        let shard = Registry::singleton().shard(value.type_id());
+        let internals = try_read(shard)?;
+        *internals.debug_fns.get(&value.type_id())?
+    };
+
+    struct Snapshot<'a>(&'a dyn Every, DebugFn);
+
+    impl Debug for Snapshot<'_> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            (self.1)(self.0, f)
+        }
+    }
+
+    Some(format!("{:?}", Snapshot(value, debug_fn)))
+}
+
+/// Converts `value` into [Every] if a vtable has been specialised for its type in the singleton
+/// [Registry]. See [`every::from_any()`](crate::every::from_any).
+pub(crate) fn from_any_registered(value: Box<dyn Any>) -> Result<Box<dyn Every>, Box<dyn Any>> {
+    let type_id = (*value).type_id();
+    let from_any = read(Registry::singleton().shard(type_id)).from_any_fns.get(&type_id).copied();
+    match from_any {
+        Some(from_any) => Ok(from_any(value)),
+        None => Err(value),
+    }
+}
+
+/// Suggests a type whose name is within a small edit distance of `target`: the `source` type if
+/// it is close enough, otherwise the closest registered value type. The name index is consulted
+/// rather than the shards, as its lock is never held while vtables are specialised.
+pub(crate) fn suggest_type_name(source: &'static str, target: &str) -> Option<&'static str> {
+    let max_distance = (target.chars().count() / 3).max(1);
+    let distance = |candidate: &str| {
+        Some(edit_distance(candidate, target))
+            .filter(|&distance| candidate != target && distance <= max_distance)
+    };
+    if distance(source).is_some() {
+        return Some(source);
+    }
+    read(&Registry::singleton().names)
+        .keys()
+        .filter_map(|&(candidate, _)| Some((distance(candidate)?, candidate)))
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between two strings, counted in chars.
+fn edit_distance(lhs: &str, rhs: &str) -> usize {
+    let rhs = rhs.chars().collect::<Vec<_>>();
+    let mut row = (0..=rhs.len()).collect::<Vec<_>>();
+    for (i, lhs_ch) in lhs.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &rhs_ch) in rhs.iter().enumerate() {
+            let substitution = diagonal + usize::from(lhs_ch != rhs_ch);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
         }
     }
+    row[rhs.len()]
+}
+
+/// Whether a [V]-type vtable has been specialised for a value of the given type.
+pub(crate) fn is_specialised<V: 'static>(type_id: TypeId) -> bool {
+    let internals = read(Registry::singleton().shard(type_id));
+    internals.types.contains_key(&(type_id, TypeId::of::<V>()))
+}
+
+/// A marker vtable recording that [T] is [Send]. Once a `Token<T, SendMarker>` has been obtained,
+/// erased [T] values may be converted with [BoxTryIntoSend](crate::every::BoxTryIntoSend).
+pub struct SendMarker;
+
+impl<T: Send> Specialise<T> for SendMarker {
+    fn specialise() -> Self {
+        Self
+    }
+}
+
+/// A marker vtable recording that [T] is [Send] and [Sync]. See [SendMarker].
+pub struct SyncMarker;
+
+impl<T: Send + Sync> Specialise<T> for SyncMarker {
+    fn specialise() -> Self {
+        Self
+    }
 }
 
 /// A static reference to a vtable of type [V]. The [T] parameter acts as proof that
 /// a [V]-type vtable has been specialised for the [T]-type value. By invoking
 /// [`Token::default()`], a [T]-specialised entry for [V] is added to the
 /// singleton [Registry].
-pub struct Token<T, V: Sync + Send + 'static>(&'static V, PhantomData<T>);
+pub struct Token<T, V: Sync + Send + 'static>(&'static V, PhantomData<fn() -> T>);
 
 impl<T, V: Sync + Send + 'static> Token<T, V> {
     pub fn vtable_ref(&self) -> &'static V {
@@ -59,57 +849,1430 @@ impl<T, V: Sync + Send + 'static> Token<T, V> {
     }
 }
 
+impl<T, V: Specialise<T> + Sync + Send + 'static> Token<T, V> {
+    /// Creates a token from a vtable that has been specialised for [T] ahead of time, typically
+    /// one residing in a `static` item. The registry is bypassed entirely, so the vtable is
+    /// neither leaked nor discoverable through the registry (e.g., for
+    /// [DowncastError](crate::every::DowncastError) snapshots).
+    #[inline]
+    pub const fn from_static(vtable: &'static V) -> Self {
+        Self(vtable, PhantomData)
+    }
+}
+
+impl<T, V: Sync + Send + 'static> Clone for Token<T, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, V: Sync + Send + 'static> Copy for Token<T, V> {}
+
+impl<T, V: Sync + Send + 'static> Debug for Token<T, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Token<{}, {}>", any::type_name::<T>(), any::type_name::<V>())
+    }
+}
+
+/// Tokens are equal if they refer to the same vtable.
+impl<T, V: Sync + Send + 'static> PartialEq for Token<T, V> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self.0, other.0)
+    }
+}
+
+impl<T, V: Sync + Send + 'static> Eq for Token<T, V> {}
+
 impl<T, V: Sync + Send + 'static> Token<T, V> {
-    fn create_unchecked(vtable: &'static V) -> Self {
+    pub(crate) fn create_unchecked(vtable: &'static V) -> Self {
         Self(vtable, PhantomData)
     }
 }
 
+impl<T: 'static, V: Specialise<T> + Sync + Send + 'static> Token<T, V> {
+    /// Obtains a token from the given `registry`, adding a [T]-specialised entry for [V] if
+    /// one is not yet present.
+    pub fn in_registry(registry: &Registry) -> Self {
+        let token = registry.get_or_create::<T, V>();
+        *token
+    }
+}
+
+/// A composite vtable, referring to the vtables of the tokens it was [joined](Token::join) from.
+pub type Joined<A, B> = (&'static A, &'static B);
+
+impl<T, A: Sync + Send + 'static> Token<T, A> {
+    /// Combines this token with `other` into a single token referring to both of their vtables,
+    /// wherever those came from. Joining the same pair of vtables again yields an equal token.
+    pub fn join<B: Sync + Send + 'static>(self, other: Token<T, B>) -> Token<T, Joined<A, B>> {
+        type Key = (TypeId, usize, usize);
+
+        // the type distinguishes zero-sized vtables that happen to share an address
+        static JOINED: LazyLock<RwLock<HashMap<Key, &'static (dyn Any + Sync + Send)>>> =
+            LazyLock::new(Default::default);
+
+        let key = (
+            TypeId::of::<Joined<A, B>>(),
+            ptr::from_ref(self.0).addr(),
+            ptr::from_ref(other.0).addr(),
+        );
+        let existing = read(&JOINED).get(&key).copied();
+        let joined = existing.unwrap_or_else(|| {
+            *write(&JOINED)
+                .entry(key)
+                .or_insert_with(|| Box::leak(Box::new((self.0, other.0))))
+        });
+        Token::create_unchecked(joined.downcast_ref().expect("keyed by type"))
+    }
+}
+
+impl<T, A: Sync + Send + 'static, B: Sync + Send + 'static> Token<T, Joined<A, B>> {
+    /// The token that was joined first.
+    #[inline]
+    pub fn first(&self) -> Token<T, A> {
+        Token::create_unchecked(self.0.0)
+    }
+
+    /// The token that was joined second.
+    #[inline]
+    pub fn second(&self) -> Token<T, B> {
+        Token::create_unchecked(self.0.1)
+    }
+}
+
+impl<T: 'static, V: Sync + Send + 'static> Token<T, V> {
+    /// Obtains a token from the singleton [Registry] only if a [T]-specialised entry for [V] is
+    /// already present, without adding one.
+    pub fn try_existing() -> Option<Self> {
+        Self::try_existing_in(Registry::singleton())
+    }
+
+    /// Obtains a token from the given `registry` only if a [T]-specialised entry for [V] is
+    /// already present, without adding one.
+    pub fn try_existing_in(registry: &Registry) -> Option<Self> {
+        registry.get::<T, V>().copied()
+    }
+}
+
 impl<T: 'static, V: Specialise<T> + Sync + Send + 'static> Default for Token<T, V> {
     fn default() -> Self {
-        let vtable = Registry::singleton().get_or_create::<T, V>();
-        Self::create_unchecked(vtable)
+        Self::in_registry(Registry::singleton())
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::vtable::{Registry, Specialise};
-    use std::any::TypeId;
+/// With the `serde` feature enabled, a token is serialized as the names of its value and vtable
+/// types. A token is only deserialized if the names match [T] and [V] and a [T]-specialised entry
+/// for [V] is present in the singleton [Registry]; deserialization thereby recovers the proof of
+/// registration without itself specialising the vtable.
+#[cfg(feature = "serde")]
+impl<T, V: Sync + Send + 'static> serde::Serialize for Token<T, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
 
-    impl Registry {
-        fn try_get<T: 'static, V: 'static>(&self) -> Option<&'static V> {
-            let internals = self.internals.read().unwrap();
-            let key = (TypeId::of::<T>(), TypeId::of::<V>());
-            internals
-                .types
-                .get(&key)
-                .map(|record| *record.0.downcast_ref::<&'static V>().unwrap())
-        }
+        let mut state = serializer.serialize_struct("Token", 2)?;
+        state.serialize_field("type_name", any::type_name::<T>())?;
+        state.serialize_field("vtable_type_name", any::type_name::<V>())?;
+        state.end()
     }
+}
 
-    #[test]
-    fn singleton_registry() {
-        // #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-        struct Custom;
-
-        struct VTable;
+#[cfg(feature = "serde")]
+impl<'de, T: 'static, V: Sync + Send + 'static> serde::Deserialize<'de> for Token<T, V> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
 
-        impl<T> Specialise<T> for VTable {
-            fn specialise() -> Self {
-                Self
-            }
+        #[derive(serde::Deserialize)]
+        struct Repr {
+            type_name: String,
+            vtable_type_name: String,
         }
 
-        {
-            let virtuals = Registry::singleton();
-            assert!(virtuals.try_get::<Custom, VTable>().is_none());
-            let _ = virtuals.get_or_create::<Custom, VTable>();
-            assert!(virtuals.try_get::<Custom, VTable>().is_some());
-        }
-        {
-            let virtuals = Registry::singleton();
-            assert!(virtuals.try_get::<Custom, VTable>().is_some());
+        let repr = Repr::deserialize(deserializer)?;
+        let expected = (any::type_name::<T>(), any::type_name::<V>());
+        if (repr.type_name.as_str(), repr.vtable_type_name.as_str()) != expected {
+            return Err(D::Error::custom(format!(
+                "expected token for ({}, {}), got ({}, {})",
+                expected.0, expected.1, repr.type_name, repr.vtable_type_name
+            )));
         }
+        Self::try_existing().ok_or_else(|| {
+            D::Error::custom(format!(
+                "no {} vtable registered for {}",
+                repr.vtable_type_name, repr.type_name
+            ))
+        })
     }
-}
\ No newline at end of file
+}
+
+/// Obtains a static reference to the [T]-specialised [V] token. Rust has no generic statics, so
+/// a static cannot be dedicated to each `(T, V)` pair; instead, tokens are cached in a
+/// thread-local map after the first call on each thread, sparing subsequent calls from
+/// contending on the registry. A cache hit still costs a lookup in that map, keyed by the
+/// [TypeId]s of the pair; where the types are concrete, [static_token](crate::static_token)
+/// avoids even that.
+///
+/// The cache is not borrowed while specialising, so that specialisations and registry observers
+/// may themselves acquire tokens. Cached tokens are discarded once an entry of the registry is
+/// overridden, e.g. by [`Registry::register()`].
+pub fn global_token<T: 'static, V: Specialise<T> + Sync + Send + 'static>(
+) -> &'static Token<T, V> {
+    thread_local! {
+        static TOKENS: RefCell<HashMap<(TypeId, TypeId), CachedToken, BuildTypeIdHasher>> =
+            RefCell::default();
+    }
+
+    // the thread-local cache would otherwise outlive an isolated registry
+    #[cfg(feature = "testing")]
+    if ISOLATED.get().is_some() {
+        return Registry::singleton().get_or_create::<T, V>();
+    }
+
+    let registry = Registry::singleton();
+    let generation = registry.generation.load(Ordering::Acquire);
+    let key = (TypeId::of::<T>(), TypeId::of::<V>());
+    let cached = TOKENS.with_borrow(|tokens| tokens.get(&key).copied());
+    match cached {
+        // SAFETY: the entry was cached from a leaked Token<T, V>, as attested by the key
+        Some((cached, token)) if cached == generation => unsafe { &*token.cast::<Token<T, V>>() },
+        _ => {
+            let token = registry.get_or_create::<T, V>();
+            let entry = (generation, ptr::from_ref(token).cast());
+            TOKENS.with_borrow_mut(|tokens| tokens.insert(key, entry));
+            token
+        }
+    }
+}
+
+/// A leaked, type-erased token, along with the generation of the registry when it was cached.
+type CachedToken = (u64, *const ());
+
+/// Hashes [TypeId]s, which are already uniformly distributed, by folding their bits together.
+type BuildTypeIdHasher = BuildHasherDefault<TypeIdHasher>;
+
+#[derive(Default)]
+struct TypeIdHasher(u64);
+
+impl Hasher for TypeIdHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 = fnv1a(self.0, bytes);
+    }
+
+    #[inline]
+    fn write_u64(&mut self, value: u64) {
+        self.0 = self.0.rotate_left(29) ^ value;
+    }
+}
+
+/// Types for which a [V]-type vtable can be specialised, along with a cached token for it. Generic
+/// code may require `T: HasToken<V>` in place of threading `&Token<T, V>` arguments throughout.
+pub trait HasToken<V: Sync + Send + 'static>: Sized + 'static {
+    fn token() -> &'static Token<Self, V>;
+}
+
+/// Backed by [global_token].
+impl<T: 'static, V: Specialise<T> + Sync + Send + 'static> HasToken<V> for T {
+    #[inline]
+    fn token() -> &'static Token<T, V> {
+        global_token()
+    }
+}
+
+/// Obtains a static reference to a token for concrete value and vtable types, e.g.
+/// `static_token!(MyType: ched::VTable)`. Where [global_token] cannot declare a static per `(T, V)`
+/// pair, the macro declares a [StaticToken] at each call site. The registry is consulted on the
+/// first evaluation only (and remains the source of truth for introspection); thereafter,
+/// acquiring the token costs a pair of atomic loads, until an entry of the registry is
+/// overridden.
+///
+/// Being a static, the cell cannot refer to the generic parameters of an enclosing item; generic
+/// code should use [global_token] instead.
+#[macro_export]
+macro_rules! static_token {
+    ($ty:ty : $vtable:ty) => {{
+        static TOKEN: $crate::vtable::StaticToken<$ty, $vtable> =
+            $crate::vtable::StaticToken::new();
+        TOKEN.get()
+    }};
+}
+
+/// The call-site cell declared by [static_token](crate::static_token), caching a token along with
+/// the generation of the singleton [Registry] when it was acquired. As with [global_token], the
+/// cached token is refreshed once an entry of the registry is overridden.
+pub struct StaticToken<T: 'static, V: Sync + Send + 'static>(
+    AtomicPtr<(u64, &'static Token<T, V>)>,
+);
+
+impl<T: 'static, V: Sync + Send + 'static> StaticToken<T, V> {
+    pub const fn new() -> Self {
+        Self(AtomicPtr::new(ptr::null_mut()))
+    }
+}
+
+impl<T: 'static, V: Sync + Send + 'static> Default for StaticToken<T, V> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: 'static, V: Specialise<T> + Sync + Send + 'static> StaticToken<T, V> {
+    /// Acquires the cached token, refreshing it from the singleton [Registry] if it is absent or
+    /// stale. Each refresh leaks a small entry, as a concurrent caller may still be reading the
+    /// one it replaces.
+    pub fn get(&self) -> &'static Token<T, V> {
+        // the cell would otherwise outlive an isolated registry
+        #[cfg(feature = "testing")]
+        if ISOLATED.get().is_some() {
+            return Registry::singleton().get_or_create::<T, V>();
+        }
+
+        let registry = Registry::singleton();
+        let generation = registry.generation.load(Ordering::Acquire);
+        // SAFETY: entries are leaked, and hence never freed
+        let cached = unsafe { self.0.load(Ordering::Acquire).as_ref() };
+        match cached {
+            Some(&(cached, token)) if cached == generation => token,
+            _ => {
+                let token = registry.get_or_create::<T, V>();
+                let entry = Box::leak(Box::new((generation, token)));
+                self.0.store(entry, Ordering::Release);
+                token
+            }
+        }
+    }
+}
+
+/// A vtable registration declared with [register_vtable](crate::register_vtable).
+#[cfg(feature = "inventory")]
+#[doc(hidden)]
+pub struct Registration(pub fn(&Registry));
+
+#[cfg(feature = "inventory")]
+inventory::collect!(Registration);
+
+/// Declares, at compile time, that a vtable is to be specialised for a value type, e.g.
+/// `register_vtable!(MyType: ched::VTable)`. Declarations may be distributed among any crates
+/// linked into the binary; all are collected into the singleton [Registry] when it is first
+/// accessed, sparing the declared types from lazy specialisation on first use. Requires the
+/// `inventory` feature.
+#[cfg(feature = "inventory")]
+#[macro_export]
+macro_rules! register_vtable {
+    ($ty:ty : $vtable:ty) => {
+        $crate::inventory::submit! {
+            $crate::vtable::Registration(|registry| {
+                let _ = $crate::vtable::Token::<$ty, $vtable>::in_registry(registry);
+            })
+        }
+    };
+}
+
+/// Identifies the layout of a [T]-specialised [V] vtable, as compiled into a particular binary:
+/// a hash of this crate's version and the names, sizes and alignments of [T] and [V]. Vtables
+/// exported from one dynamic library and imported into another are verified by their
+/// fingerprints, guarding against mismatched crate versions and struct layouts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Fingerprint(pub u64);
+
+impl Fingerprint {
+    pub fn of<T, V>() -> Self {
+        Self::hash::<T, V>(env!("CARGO_PKG_VERSION"))
+    }
+
+    fn hash<T, V>(version: &str) -> Self {
+        let mut hash = fnv1a(FNV_OFFSET, version.as_bytes());
+        for (name, size, align) in [
+            (any::type_name::<T>(), size_of::<T>(), align_of::<T>()),
+            (any::type_name::<V>(), size_of::<V>(), align_of::<V>()),
+        ] {
+            hash = fnv1a(hash, name.as_bytes());
+            hash = fnv1a(hash, &(size as u64).to_le_bytes());
+            hash = fnv1a(hash, &(align as u64).to_le_bytes());
+        }
+        Self(hash)
+    }
+}
+
+/// A token in a form that may be passed across a dynamic-library boundary, obtained from
+/// [`Token::export()`].
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct ExportedToken {
+    pub fingerprint: Fingerprint,
+    pub vtable: *const (),
+}
+
+/// Vtables that can be expressed in the version-independent form of the `vtable-shared` crate,
+/// and thereby shared with other versions of this crate by [shared_token].
+#[cfg(feature = "shared")]
+pub trait Share<T>: Specialise<T> + Sync + Send + 'static {
+    /// Exports the vtable of this copy of the crate, with thunks that delegate to the
+    /// [T]-specialised entry of its singleton [Registry] as of each call, so that overrides
+    /// remain in effect.
+    fn export() -> vtable_shared::SharedVTable;
+
+    /// Constructs a vtable whose thunks delegate to the one [published](published) for [T] by
+    /// another copy of the crate.
+    fn adopt() -> Self;
+}
+
+/// With the `shared` feature enabled, obtains a token whose vtable is shared with every other
+/// copy of this crate linked into the binary, of any version, by way of a process-global table
+/// keyed by the names of [T] and [V] (see the `vtable-shared` crate). The first copy to request
+/// a given pair [exports](Share::export) its vtable, which continues to reflect the entry of its
+/// singleton [Registry]. Other copies [adopt](Share::adopt) it, registering a vtable that
+/// delegates to it in their own singletons; tokens obtained from those beforehand are unaffected.
+/// If the names were published for a different type, the vtable is specialised locally instead.
+#[cfg(feature = "shared")]
+pub fn shared_token<T: 'static, V: Share<T>>() -> Token<T, V> {
+    use std::sync::{Mutex, PoisonError};
+    use vtable_shared::Lookup;
+
+    static ADOPTED: LazyLock<Mutex<HashSet<(TypeId, TypeId)>>> = LazyLock::new(Default::default);
+
+    let lookup = vtable_shared::get_or_insert(
+        any::type_name::<T>(),
+        any::type_name::<V>(),
+        TypeId::of::<T>(),
+        V::export,
+    );
+    if let Lookup::Found(_) = lookup {
+        // held while registering, so that racing callers do not obtain the local vtable
+        let mut adopted = ADOPTED.lock().unwrap_or_else(PoisonError::into_inner);
+        if adopted.insert((TypeId::of::<T>(), TypeId::of::<V>())) {
+            return Registry::singleton().register(V::adopt());
+        }
+    }
+    Token::default()
+}
+
+/// The vtable published for [T] under the name of [V], cached per thread once found.
+///
+/// # Panics
+/// If none has been published, which cannot be the case for a vtable that was
+/// [adopted](Share::adopt) by [shared_token].
+#[cfg(feature = "shared")]
+pub fn published<T: 'static, V: 'static>() -> &'static vtable_shared::SharedVTable {
+    type Published = &'static vtable_shared::SharedVTable;
+
+    thread_local! {
+        static PUBLISHED: RefCell<HashMap<(TypeId, TypeId), Published, BuildTypeIdHasher>> =
+            RefCell::default();
+    }
+
+    let key = (TypeId::of::<T>(), TypeId::of::<V>());
+    if let Some(published) = PUBLISHED.with_borrow(|published| published.get(&key).copied()) {
+        return published;
+    }
+    let published = vtable_shared::get(any::type_name::<T>(), any::type_name::<V>())
+        .filter(|published| published.type_id == TypeId::of::<T>())
+        .unwrap_or_else(|| panic!("no vtable published for {}", any::type_name::<T>()));
+    PUBLISHED.with_borrow_mut(|cache| cache.insert(key, published));
+    published
+}
+
+/// An [ExportedToken] was imported as a token whose fingerprint differs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FingerprintMismatch {
+    pub expected: Fingerprint,
+    pub actual: Fingerprint,
+}
+
+impl Display for FingerprintMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "vtable fingerprint mismatch: expected {:016x}, got {:016x}",
+            self.expected.0, self.actual.0
+        )
+    }
+}
+
+impl Error for FingerprintMismatch {}
+
+impl<T, V: Sync + Send + 'static> Token<T, V> {
+    /// The fingerprint of the vtable's layout.
+    #[inline]
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::of::<T, V>()
+    }
+
+    /// Exports the token, stamped with its fingerprint, for passing to another dynamic library.
+    pub fn export(&self) -> ExportedToken {
+        ExportedToken {
+            fingerprint: self.fingerprint(),
+            vtable: ptr::from_ref(self.0).cast(),
+        }
+    }
+
+    /// Imports a token exported by [`Token::export()`], verifying its fingerprint.
+    ///
+    /// # Safety
+    /// The exported vtable pointer must remain valid for the life of the process, as is the case
+    /// for tokens exported from a library that is never unloaded. The fingerprint guards against
+    /// differing layouts, not against fabricated or dangling pointers.
+    pub unsafe fn import(exported: ExportedToken) -> Result<Self, FingerprintMismatch> {
+        let expected = Fingerprint::of::<T, V>();
+        if exported.fingerprint != expected {
+            return Err(FingerprintMismatch {
+                expected,
+                actual: exported.fingerprint,
+            });
+        }
+        // SAFETY: the fingerprint guarantees a matching layout; validity is the caller's obligation
+        let vtable = unsafe { &*exported.vtable.cast::<V>() };
+        Ok(Self::create_unchecked(vtable))
+    }
+}
+
+/// A vtable entry that may be filled in after the vtable has been specialised, supporting layered
+/// architectures in which capabilities are attached as their layers initialise. For example, a
+/// vtable may carry a `serialize: Late<SerializeFn>` entry, left empty by
+/// [`Specialise::specialise()`] and attached later by a serialization layer:
+///
+/// ```
+/// use vtable::vtable::{Late, Specialise, Token};
+///
+/// struct VTable {
+///     describe: Late<fn() -> &'static str>,
+/// }
+///
+/// impl<T> Specialise<T> for VTable {
+///     fn specialise() -> Self {
+///         Self { describe: Late::new() }
+///     }
+/// }
+///
+/// let vtable = Token::<u8, VTable>::default().vtable_ref();
+/// assert!(vtable.describe.get().is_none());
+/// vtable.describe.attach(|| "a byte").unwrap();
+/// assert_eq!("a byte", vtable.describe.require("describe").unwrap()());
+/// ```
+///
+/// An entry may be attached at most once.
+pub struct Late<F>(OnceLock<F>);
+
+impl<F: Copy> Late<F> {
+    /// Creates an empty entry.
+    pub const fn new() -> Self {
+        Self(OnceLock::new())
+    }
+
+    /// Creates an entry that is already filled in.
+    pub fn ready(f: F) -> Self {
+        Self(OnceLock::from(f))
+    }
+
+    /// Fills in the entry, returning `f` back if the entry has already been filled.
+    pub fn attach(&self, f: F) -> Result<(), F> {
+        self.0.set(f)
+    }
+
+    /// The entry, if it has been filled in.
+    #[inline]
+    pub fn get(&self) -> Option<F> {
+        self.0.get().copied()
+    }
+
+    /// The entry, or a [MissingCapability] naming it if it has not been filled in.
+    #[inline]
+    pub fn require(&self, capability: &'static str) -> Result<F, MissingCapability> {
+        self.get().ok_or(MissingCapability(capability))
+    }
+}
+
+impl<F: Copy> Default for Late<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> Debug for Late<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(if self.0.get().is_some() { "Late(..)" } else { "Late(<missing>)" })
+    }
+}
+
+/// A [Late] vtable entry was required before it was filled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingCapability(pub &'static str);
+
+impl Display for MissingCapability {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "vtable entry '{}' has not been attached", self.0)
+    }
+}
+
+impl Error for MissingCapability {}
+
+/// A registry mode that retains vtables in [Arc]s rather than leaking them, allowing entries to be
+/// removed with [`ArcRegistry::unregister()`] — e.g., before unloading a dynamically loaded plugin
+/// whose code the vtables refer to. Outstanding [ArcToken]s keep their vtable alive after it has
+/// been unregistered.
+///
+/// A registry created with [`ArcRegistry::weak()`] retains no vtables of its own: each vtable is
+/// dropped along with the last of its tokens, and is specialised afresh if requested again.
+#[derive(Default)]
+pub struct ArcRegistry {
+    vtables: RwLock<HashMap<(TypeId, TypeId), Retained>>,
+    weak: bool,
+}
+
+enum Retained {
+    Strong(Arc<dyn Any + Sync + Send>),
+    Weak(Weak<dyn Any + Sync + Send>),
+}
+
+impl Retained {
+    fn upgrade(&self) -> Option<Arc<dyn Any + Sync + Send>> {
+        match self {
+            Retained::Strong(vtable) => Some(vtable.clone()),
+            Retained::Weak(vtable) => vtable.upgrade(),
+        }
+    }
+}
+
+impl ArcRegistry {
+    /// Creates an empty registry that retains its vtables until they are unregistered.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty registry that retains its vtables only for as long as tokens refer to
+    /// them.
+    pub fn weak() -> Self {
+        Self {
+            weak: true,
+            ..Self::default()
+        }
+    }
+
+    /// Obtains a token for the [T]-specialised [V] vtable, specialising it if one is not yet
+    /// present.
+    pub fn get_or_create<T: 'static, V: Specialise<T> + Sync + Send + 'static>(
+        &self,
+    ) -> ArcToken<T, V> {
+        let key = (TypeId::of::<T>(), TypeId::of::<V>());
+        if let Some(vtable) = read(&self.vtables).get(&key).and_then(Retained::upgrade) {
+            return ArcToken::create_unchecked(vtable);
+        }
+
+        let mut vtables = write(&self.vtables);
+        if let Some(vtable) = vtables.get(&key).and_then(Retained::upgrade) {
+            return ArcToken::create_unchecked(vtable);
+        }
+        let vtable: Arc<dyn Any + Sync + Send> = Arc::new(V::specialise());
+        let retained = if self.weak {
+            // dead entries would otherwise pin their allocations for the life of the registry
+            vtables.retain(|_, retained| retained.upgrade().is_some());
+            Retained::Weak(Arc::downgrade(&vtable))
+        } else {
+            Retained::Strong(vtable.clone())
+        };
+        vtables.insert(key, retained);
+        ArcToken::create_unchecked(vtable)
+    }
+
+    /// Removes the [T]-specialised [V] vtable, returning `true` if it was present.
+    pub fn unregister<T: 'static, V: 'static>(&self) -> bool {
+        let key = (TypeId::of::<T>(), TypeId::of::<V>());
+        let removed = write(&self.vtables).remove(&key);
+        removed.is_some_and(|retained| retained.upgrade().is_some())
+    }
+}
+
+/// A reference-counted counterpart of [Token], issued by an [ArcRegistry].
+pub struct ArcToken<T, V: Sync + Send + 'static>(Arc<V>, PhantomData<fn() -> T>);
+
+impl<T, V: Sync + Send + 'static> ArcToken<T, V> {
+    pub fn vtable_ref(&self) -> &V {
+        &self.0
+    }
+
+    fn create_unchecked(vtable: Arc<dyn Any + Sync + Send>) -> Self {
+        Self(vtable.downcast().unwrap(), PhantomData)
+    }
+}
+
+impl<T, V: Sync + Send + 'static> Clone for ArcToken<T, V> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vtable::{
+        debug_snapshot, edit_distance, global_token, read, ArcRegistry, Counters, ExportedToken,
+        Fingerprint, FingerprintMismatch, HasToken, Late, MissingCapability, PreloadError,
+        Registry, RegistryStats, Specialise, Token, VTableId,
+    };
+    use std::any;
+    use std::any::{Any, TypeId};
+    use std::panic;
+    use std::ptr;
+    use std::sync::atomic::Ordering;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::SystemTime;
+
+    /// The vtable shared by most tests, specialised as `VTable(0)` for any type. Tests touching
+    /// the singleton pair it with value types of their own, so as not to observe each other's
+    /// entries.
+    struct VTable(u8);
+
+    impl<T> Specialise<T> for VTable {
+        fn specialise() -> Self {
+            Self(0)
+        }
+    }
+
+    #[test]
+    fn singleton_registry() {
+        // #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Custom;
+
+        {
+            let virtuals = Registry::singleton();
+            assert!(virtuals.get::<Custom, VTable>().is_none());
+            let _ = virtuals.get_or_create::<Custom, VTable>();
+            assert!(virtuals.get::<Custom, VTable>().is_some());
+        }
+        {
+            let virtuals = Registry::singleton();
+            assert!(virtuals.get::<Custom, VTable>().is_some());
+        }
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(0, edit_distance("", ""));
+        assert_eq!(3, edit_distance("", "u32"));
+        assert_eq!(0, edit_distance("u32", "u32"));
+        assert_eq!(1, edit_distance("u32", "i32"));
+        assert_eq!(2, edit_distance("u32", "u8"));
+        assert_eq!(3, edit_distance("kitten", "sitting"));
+    }
+
+    #[test]
+    fn concurrent_get_or_create() {
+        struct Custom;
+
+        let vtables = (0..8)
+            .map(|_| thread::spawn(|| Token::<Custom, VTable>::default().vtable_ref()))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>();
+        assert!(vtables.iter().all(|vtable| ptr::eq(*vtable, vtables[0])));
+    }
+
+    #[test]
+    fn sharded_by_value_type() {
+        let registry = Registry::default();
+        let shard = registry.shard(TypeId::of::<u8>());
+        assert!(ptr::eq(shard, registry.shard(TypeId::of::<u8>())));
+
+        let distinct = [
+            TypeId::of::<u8>(),
+            TypeId::of::<u16>(),
+            TypeId::of::<u32>(),
+            TypeId::of::<u64>(),
+            TypeId::of::<i8>(),
+            TypeId::of::<i16>(),
+            TypeId::of::<i32>(),
+            TypeId::of::<i64>(),
+        ]
+        .iter()
+        .filter(|&&type_id| !ptr::eq(shard, registry.shard(type_id)))
+        .count();
+        assert!(distinct > 0);
+    }
+
+    #[test]
+    fn global_token_cached() {
+        struct Custom;
+
+        let token = global_token::<Custom, VTable>();
+        assert!(ptr::eq(token, global_token::<Custom, VTable>()));
+        assert!(ptr::eq(
+            token.vtable_ref(),
+            Token::<Custom, VTable>::default().vtable_ref()
+        ));
+        let other_thread = thread::spawn(|| global_token::<Custom, VTable>() as *const _ as usize);
+        assert_eq!(token as *const _ as usize, other_thread.join().unwrap());
+    }
+
+    #[test]
+    fn global_token_reentrant() {
+        struct Outer;
+
+        struct Inner;
+
+        struct VTable(u8);
+
+        impl Specialise<Outer> for VTable {
+            fn specialise() -> Self {
+                Self(global_token::<Inner, VTable>().vtable_ref().0 + 1)
+            }
+        }
+
+        impl Specialise<Inner> for VTable {
+            fn specialise() -> Self {
+                Self(1)
+            }
+        }
+
+        assert_eq!(2, global_token::<Outer, VTable>().vtable_ref().0);
+        assert_eq!(1, global_token::<Inner, VTable>().vtable_ref().0);
+    }
+
+    #[test]
+    fn instance_registries_isolated() {
+        struct Custom;
+
+        let registry_1 = Registry::new();
+        let registry_2 = Registry::new();
+        let token_1 = Token::<Custom, VTable>::in_registry(&registry_1);
+        assert!(registry_1.get::<Custom, VTable>().is_some());
+        assert!(registry_2.get::<Custom, VTable>().is_none());
+        assert!(Registry::singleton().get::<Custom, VTable>().is_none());
+
+        assert!(ptr::eq(
+            token_1.vtable_ref(),
+            Token::<Custom, VTable>::in_registry(&registry_1).vtable_ref()
+        ));
+        let token_2 = Token::<Custom, VTable>::in_registry(&registry_2);
+        assert!(!ptr::eq(token_1.vtable_ref(), token_2.vtable_ref()));
+    }
+
+    #[test]
+    fn entries() {
+        struct Custom;
+
+        let registry = Registry::new();
+        assert!(registry.entries().is_empty());
+        let before = SystemTime::now();
+        let _ = Token::<Custom, VTable>::in_registry(&registry);
+        let _ = Token::<u8, VTable>::in_registry(&registry);
+
+        let entries = registry.entries();
+        assert_eq!(
+            vec![
+                (any::type_name::<Custom>(), any::type_name::<VTable>()),
+                ("u8", any::type_name::<VTable>())
+            ],
+            entries
+                .iter()
+                .map(|entry| (entry.type_name, entry.vtable_type_name))
+                .collect::<Vec<_>>()
+        );
+        assert!(entries.iter().all(|entry| entry.registered_at >= before));
+    }
+
+    #[test]
+    fn arc_registry_unregister() {
+        struct Custom;
+
+        let registry = ArcRegistry::new();
+        let token = registry.get_or_create::<Custom, VTable>();
+        assert!(ptr::eq(
+            token.vtable_ref(),
+            registry.get_or_create::<Custom, VTable>().vtable_ref()
+        ));
+
+        assert!(registry.unregister::<Custom, VTable>());
+        assert!(!registry.unregister::<Custom, VTable>());
+        assert_eq!(0, token.clone().vtable_ref().0);
+
+        let recreated = registry.get_or_create::<Custom, VTable>();
+        assert!(!ptr::eq(token.vtable_ref(), recreated.vtable_ref()));
+    }
+
+    #[test]
+    fn stats() {
+        struct VTable([u64; 4]);
+
+        impl<T> Specialise<T> for VTable {
+            fn specialise() -> Self {
+                Self([0; 4])
+            }
+        }
+
+        let registry = Registry::new();
+        assert_eq!(RegistryStats::default(), registry.stats());
+        let token = Token::<u8, VTable>::in_registry(&registry);
+        let _ = Token::<u8, VTable>::in_registry(&registry);
+        let _ = Token::<u16, VTable>::in_registry(&registry);
+        assert_eq!(0, token.vtable_ref().0[0]);
+        assert_eq!(
+            RegistryStats {
+                entries: 2,
+                leaked_bytes: 2 * (32 + size_of::<Token<u8, VTable>>()),
+            },
+            registry.stats()
+        );
+    }
+
+    #[test]
+    fn specialise_with_context() {
+        struct Seed(u64);
+
+        struct VTable(u64);
+
+        impl<T> Specialise<T> for VTable {
+            fn specialise() -> Self {
+                Self(0)
+            }
+
+            fn specialise_with(context: &dyn Any) -> Self {
+                match context.downcast_ref::<Seed>() {
+                    None => <Self as Specialise<T>>::specialise(),
+                    Some(seed) => Self(seed.0),
+                }
+            }
+        }
+
+        let registry = Registry::with_context(Seed(42));
+        assert_eq!(42, Token::<u8, VTable>::in_registry(&registry).vtable_ref().0);
+        assert_eq!(0, Token::<u8, VTable>::in_registry(&Registry::new()).vtable_ref().0);
+    }
+
+    #[test]
+    fn register_override() {
+        let registry = Registry::new();
+        let specialised = Token::<u8, VTable>::in_registry(&registry);
+        let overridden = registry.register::<u8, VTable>(VTable(42));
+        assert_eq!(0, specialised.vtable_ref().0);
+        assert_eq!(42, overridden.vtable_ref().0);
+        assert_eq!(42, Token::<u8, VTable>::in_registry(&registry).vtable_ref().0);
+        assert_eq!(0, Token::<u16, VTable>::in_registry(&registry).vtable_ref().0);
+        assert_eq!(1, registry.entries().iter().filter(|entry| entry.type_name == "u8").count());
+    }
+
+    #[test]
+    fn token_traits() {
+        let registry = Registry::new();
+        let token = Token::<u8, VTable>::in_registry(&registry);
+        let copied = token;
+        assert_eq!(token, copied);
+        assert_eq!(token, Token::in_registry(&registry));
+        assert_ne!(token, Token::in_registry(&Registry::new()));
+        assert_eq!(
+            format!("Token<u8, {}>", any::type_name::<VTable>()),
+            format!("{token:?}")
+        );
+    }
+
+    #[test]
+    fn try_existing() {
+        struct Custom;
+
+        assert_eq!(None, Token::<Custom, VTable>::try_existing());
+        let token = Token::<Custom, VTable>::default();
+        assert_eq!(Some(token), Token::try_existing());
+
+        let registry = Registry::new();
+        assert_eq!(None, Token::<Custom, VTable>::try_existing_in(&registry));
+        assert!(registry.get::<Custom, VTable>().is_none());
+        let token = Token::<Custom, VTable>::in_registry(&registry);
+        assert_eq!(Some(token), Token::try_existing_in(&registry));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn token_serde() {
+        struct Registered;
+
+        struct Unregistered;
+
+        let token = Token::<Registered, VTable>::default();
+        let json = serde_json::to_string(&token).unwrap();
+        assert_eq!(
+            format!(
+                r#"{{"type_name":"{}","vtable_type_name":"{}"}}"#,
+                any::type_name::<Registered>(),
+                any::type_name::<VTable>()
+            ),
+            json
+        );
+        assert_eq!(token, serde_json::from_str(&json).unwrap());
+
+        let err = serde_json::from_str::<Token<Unregistered, VTable>>(&json).unwrap_err();
+        assert!(err.to_string().starts_with("expected token for"), "{err}");
+
+        let elsewhere = Token::<Unregistered, VTable>::in_registry(&Registry::new());
+        let json = serde_json::to_string(&elsewhere).unwrap();
+        let err = serde_json::from_str::<Token<Unregistered, VTable>>(&json).unwrap_err();
+        assert!(err.to_string().starts_with("no "), "{err}");
+    }
+
+    #[test]
+    fn survives_panicking_specialisation() {
+        struct Custom;
+
+        struct Panicky;
+
+        impl<T> Specialise<T> for Panicky {
+            fn specialise() -> Self {
+                panic!("cannot specialise")
+            }
+        }
+
+        let registry = Registry::new();
+        let result = panic::catch_unwind(|| Token::<Custom, Panicky>::in_registry(&registry));
+        assert!(result.is_err());
+        assert!(Token::<Custom, Panicky>::try_existing_in(&registry).is_none());
+        assert_eq!(0, Token::<Custom, VTable>::in_registry(&registry).vtable_ref().0);
+        assert_eq!(1, registry.stats().entries);
+    }
+
+    #[test]
+    fn resolve_by_name() {
+        struct Other;
+
+        let registry = Registry::new();
+        let token = Token::<u8, VTable>::in_registry(&registry);
+        assert!(ptr::eq(token.vtable_ref(), registry.resolve::<VTable>("u8").unwrap()));
+        assert!(registry.resolve::<VTable>("u16").is_none());
+        assert!(registry.resolve::<Other>("u8").is_none());
+
+        registry.register::<u8, VTable>(VTable(42));
+        assert_eq!(42, registry.resolve::<VTable>("u8").unwrap().0);
+    }
+
+    #[test]
+    fn preload() {
+        let registry = Registry::new();
+        let registrations: [fn(&Registry); 3] = [
+            |registry| {
+                Token::<u8, VTable>::in_registry(registry);
+            },
+            |_| panic!("missing configuration"),
+            |registry| {
+                Token::<u16, VTable>::in_registry(registry);
+            },
+        ];
+        assert_eq!(
+            Err(PreloadError {
+                index: 1,
+                message: Some("missing configuration".into()),
+            }),
+            registry.preload(registrations)
+        );
+        assert!(Token::<u8, VTable>::try_existing_in(&registry).is_some());
+        assert!(Token::<u16, VTable>::try_existing_in(&registry).is_none());
+        assert_eq!(Ok(()), registry.preload(&registrations[2..]));
+    }
+
+    #[test]
+    fn static_token() {
+        struct Custom;
+
+        fn acquire() -> &'static Token<Custom, VTable> {
+            crate::static_token!(Custom: VTable)
+        }
+
+        let token = acquire();
+        assert!(ptr::eq(token, acquire()));
+        assert_eq!(Some(*token), Token::try_existing());
+
+        let overridden = Registry::singleton().register::<Custom, VTable>(VTable(7));
+        assert!(ptr::eq(overridden.vtable_ref(), acquire().vtable_ref()));
+        assert_eq!(7, acquire().vtable_ref().0);
+    }
+
+    #[test]
+    fn composite_token() {
+        struct Custom;
+
+        struct Left(u8);
+
+        impl<T> Specialise<T> for Left {
+            fn specialise() -> Self {
+                Self(1)
+            }
+        }
+
+        struct Right(u8);
+
+        impl<T> Specialise<T> for Right {
+            fn specialise() -> Self {
+                Self(2)
+            }
+        }
+
+        let left = Token::<Custom, Left>::default();
+        let right = Token::<Custom, Right>::default();
+        let composite = left.join(right);
+        assert_eq!(composite, left.join(right));
+        assert_eq!(left, composite.first());
+        assert_eq!(right, composite.second());
+        assert_eq!(1, composite.first().vtable_ref().0);
+        assert_eq!(2, composite.second().vtable_ref().0);
+
+        // the vtables of the joined tokens are retained, whichever registry they came from
+        let registry = Registry::new();
+        let overridden = registry.register::<Custom, _>(Right(3));
+        let composite = left.join(overridden);
+        assert_eq!(overridden, composite.second());
+        assert_eq!(3, composite.second().vtable_ref().0);
+        assert_ne!(composite, left.join(right));
+
+        let owned = Token::<Custom, (Left, Right)>::default();
+        assert_eq!((1, 2), (owned.vtable_ref().0.0, owned.vtable_ref().1.0));
+    }
+
+    #[test]
+    fn metrics() {
+        let registry = Registry::new();
+        let counters = Arc::new(Counters::default());
+        assert!(registry.install_metrics(counters.clone()).is_ok());
+        assert!(registry.install_metrics(Counters::default()).is_err());
+
+        let _ = Token::<u8, VTable>::in_registry(&registry);
+        let _ = Token::<u8, VTable>::in_registry(&registry);
+        let _ = Token::<u16, VTable>::in_registry(&registry);
+        assert_eq!(1, counters.hits.load(Ordering::Relaxed));
+        assert_eq!(2, counters.misses.load(Ordering::Relaxed));
+        assert_eq!(2, counters.specialisations.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn metrics_inspect_registry() {
+        use crate::vtable::RegistryMetrics;
+        use std::sync::atomic::AtomicUsize;
+        use std::time::Duration;
+
+        static SEEN: AtomicUsize = AtomicUsize::new(0);
+
+        // would deadlock if the sink were invoked with a shard lock held
+        struct Inspecting(&'static Registry);
+
+        impl RegistryMetrics for Inspecting {
+            fn specialised(&self, _: &'static str, _: &'static str) {
+                SEEN.store(self.0.entries().len(), Ordering::Relaxed);
+            }
+
+            fn lock_wait(&self, _: Duration) {
+                self.0.dump();
+            }
+        }
+
+        let registry: &'static Registry = Box::leak(Box::default());
+        assert!(registry.install_metrics(Inspecting(registry)).is_ok());
+        let _ = Token::<u8, VTable>::in_registry(registry);
+        let _ = Token::<u16, VTable>::in_registry(registry);
+        assert_eq!(2, SEEN.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn fingerprints() {
+        struct Other(#[allow(dead_code)] u8);
+
+        assert_eq!(Fingerprint::of::<u8, VTable>(), Fingerprint::of::<u8, VTable>());
+        assert_ne!(Fingerprint::of::<u8, VTable>(), Fingerprint::of::<u16, VTable>());
+        assert_ne!(Fingerprint::of::<u8, VTable>(), Fingerprint::of::<u8, Other>());
+
+        let token = Token::<u8, VTable>::in_registry(&Registry::new());
+        let exported = token.export();
+        let imported = unsafe { Token::<u8, VTable>::import(exported) }.unwrap();
+        assert_eq!(token, imported);
+        assert_eq!(0, imported.vtable_ref().0);
+
+        let forged = ExportedToken {
+            fingerprint: Fingerprint(0),
+            ..exported
+        };
+        assert_eq!(
+            Err(FingerprintMismatch {
+                expected: token.fingerprint(),
+                actual: Fingerprint(0),
+            }),
+            unsafe { Token::<u8, VTable>::import(forged) }
+        );
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn isolate() {
+        struct Custom;
+
+        let global = Token::<Custom, VTable>::default();
+        {
+            let _outer = Registry::isolate();
+            assert_eq!(None, Token::<Custom, VTable>::try_existing());
+            let isolated = *global_token::<Custom, VTable>();
+            assert_ne!(global, isolated);
+            {
+                let _inner = Registry::isolate();
+                assert_eq!(None, Token::<Custom, VTable>::try_existing());
+            }
+            assert_eq!(Some(isolated), Token::try_existing());
+        }
+        assert_eq!(Some(global), Token::try_existing());
+    }
+
+    #[test]
+    fn has_token() {
+        struct Custom;
+
+        fn vtable_of<T: HasToken<VTable>>(_value: &T) -> &'static VTable {
+            T::token().vtable_ref()
+        }
+
+        assert_eq!(0, vtable_of(&Custom).0);
+        assert!(ptr::eq(
+            vtable_of(&Custom),
+            Token::<Custom, VTable>::default().vtable_ref()
+        ));
+    }
+
+    #[test]
+    fn vtable_ids() {
+        struct Other;
+
+        let registry = Registry::new();
+        assert_eq!(VTableId(0), registry.id::<u8, VTable>());
+        assert_eq!(VTableId(1), registry.id::<u16, VTable>());
+        assert_eq!(VTableId(0), registry.id::<u8, VTable>());
+
+        let token = Token::<u16, VTable>::in_registry(&registry);
+        assert!(ptr::eq(token.vtable_ref(), registry.resolve_id(VTableId(1)).unwrap()));
+        assert!(registry.resolve_id::<Other>(VTableId(1)).is_none());
+        assert!(registry.resolve_id::<VTable>(VTableId(2)).is_none());
+
+        registry.register::<u8, VTable>(VTable(42));
+        assert_eq!(VTableId(0), registry.id::<u8, VTable>());
+        assert_eq!(42, registry.resolve_id::<VTable>(VTableId(0)).unwrap().0);
+        assert!(registry.resolve_id::<VTable>(VTableId(2)).is_none());
+
+        registry.register::<u32, VTable>(VTable(7));
+        assert_eq!(VTableId(2), registry.id::<u32, VTable>());
+    }
+
+    #[test]
+    fn dump() {
+        let registry = Registry::new();
+        assert_eq!("", registry.dump());
+        let _ = Token::<u16, VTable>::in_registry(&registry);
+        let _ = Token::<u8, VTable>::in_registry(&registry);
+        let vtable = any::type_name::<VTable>();
+        assert_eq!(format!("#0: u16 => {vtable}\n#1: u8 => {vtable}\n"), registry.dump());
+
+        let debug = format!("{registry:?}");
+        let expected = "Registry { entries: [EntryInfo { type_name: \"u16\"";
+        assert!(debug.starts_with(expected), "{debug}");
+
+        let internals = read(registry.shard(TypeId::of::<u8>()));
+        assert!(format!("{internals:?}").contains(&format!("(\"u8\", \"{vtable}\")")));
+    }
+
+    #[test]
+    fn observe() {
+        let registry = Arc::new(Registry::new());
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        registry.observe({
+            let registry = registry.clone();
+            let observed = observed.clone();
+            move |type_name, vtable_type_name| {
+                // observers may use the registry without deadlocking
+                assert!(registry.resolve::<VTable>(type_name).is_some());
+                observed.lock().unwrap().push((type_name, vtable_type_name));
+            }
+        });
+
+        let _ = Token::<u8, VTable>::in_registry(&registry);
+        let _ = Token::<u8, VTable>::in_registry(&registry);
+        let _ = Token::<u16, VTable>::in_registry(&registry);
+        let vtable = any::type_name::<VTable>();
+        assert_eq!(vec![("u8", vtable), ("u16", vtable)], *observed.lock().unwrap());
+    }
+
+    #[test]
+    fn late_entries() {
+        struct VTable {
+            size: fn() -> usize,
+            name: Late<fn() -> &'static str>,
+        }
+
+        impl<T> Specialise<T> for VTable {
+            fn specialise() -> Self {
+                Self {
+                    size: size_of::<T>,
+                    name: Late::new(),
+                }
+            }
+        }
+
+        let registry = Registry::new();
+        let vtable = Token::<u16, VTable>::in_registry(&registry).vtable_ref();
+        assert_eq!(2, (vtable.size)());
+        assert_eq!(Err(MissingCapability("name")), vtable.name.require("name"));
+        assert_eq!("Late(<missing>)", format!("{:?}", vtable.name));
+
+        assert!(vtable.name.attach(any::type_name::<u16>).is_ok());
+        assert!(vtable.name.attach(any::type_name::<u8>).is_err());
+        let again = Token::<u16, VTable>::in_registry(&registry).vtable_ref();
+        assert_eq!("u16", again.name.require("name").unwrap()());
+        assert_eq!(Some(5), Late::ready(5).get());
+    }
+
+    #[test]
+    fn weak_arc_registry() {
+        struct Custom;
+
+        struct VTable(#[allow(dead_code)] Arc<()>);
+
+        impl<T> Specialise<T> for VTable {
+            fn specialise() -> Self {
+                Self(LIVE.with(Arc::clone))
+            }
+        }
+
+        thread_local! {
+            static LIVE: Arc<()> = Arc::new(());
+        }
+
+        let live = || LIVE.with(Arc::strong_count) - 1;
+        let registry = ArcRegistry::weak();
+        let token = registry.get_or_create::<Custom, VTable>();
+        let cloned = token.clone();
+        assert!(ptr::eq(
+            token.vtable_ref(),
+            registry.get_or_create::<Custom, VTable>().vtable_ref()
+        ));
+        assert_eq!(1, live());
+
+        drop(token);
+        assert_eq!(1, live());
+        drop(cloned);
+        assert_eq!(0, live());
+        assert!(!registry.unregister::<Custom, VTable>());
+
+        let _recreated = registry.get_or_create::<Custom, VTable>();
+        assert_eq!(1, live());
+    }
+
+    #[test]
+    fn arc_registry_weak_prunes() {
+        struct Custom<const N: usize>;
+
+        fn instantiate<const N: usize>(registry: &ArcRegistry) {
+            for _ in 0..10 {
+                drop(registry.get_or_create::<Custom<N>, VTable>());
+            }
+        }
+
+        let registry = ArcRegistry::weak();
+        instantiate::<0>(&registry);
+        instantiate::<1>(&registry);
+        instantiate::<2>(&registry);
+        instantiate::<3>(&registry);
+        let retained = registry.get_or_create::<Custom<4>, VTable>();
+        instantiate::<5>(&registry);
+        assert_eq!(2, read(&registry.vtables).len());
+        assert!(ptr::eq(
+            retained.vtable_ref(),
+            registry.get_or_create::<Custom<4>, VTable>().vtable_ref()
+        ));
+    }
+
+    #[test]
+    fn types_for() {
+        struct Other;
+
+        impl<T> Specialise<T> for Other {
+            fn specialise() -> Self {
+                Self
+            }
+        }
+
+        let registry = Registry::new();
+        let token = Token::<u16, VTable>::in_registry(&registry);
+        let _ = Token::<u8, Other>::in_registry(&registry);
+        let _ = Token::<u8, VTable>::in_registry(&registry);
+
+        let types = registry.types_for::<VTable>();
+        assert_eq!(
+            vec![(TypeId::of::<u16>(), "u16"), (TypeId::of::<u8>(), "u8")],
+            types
+                .iter()
+                .map(|specialised| (specialised.type_id, specialised.type_name))
+                .collect::<Vec<_>>()
+        );
+        assert!(ptr::eq(token.vtable_ref(), types[0].vtable));
+        assert_eq!(1, registry.types_for::<Other>().len());
+    }
+
+    #[cfg(feature = "parking_lot")]
+    #[test]
+    fn parking_lot_snapshot_contention() {
+        use crate::vtable::{try_read, write};
+
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Contended(u32);
+
+        Token::<Contended, crate::ched::VTable>::default();
+        let shard = Registry::singleton().shard(TypeId::of::<Contended>());
+        assert_eq!(Some(String::from("Contended(1)")), debug_snapshot(&Contended(1)));
+
+        let guard = write(shard);
+        assert!(try_read(shard).is_none());
+        assert_eq!(None, debug_snapshot(&Contended(2)));
+        drop(guard);
+
+        let guard = read(shard);
+        assert!(try_read(shard).is_some());
+        assert_eq!(Some(String::from("Contended(3)")), debug_snapshot(&Contended(3)));
+        drop(guard);
+    }
+
+    #[test]
+    fn redact() {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Secret(u32);
+
+        let registry = Registry::singleton();
+        registry.redact::<Secret>();
+        Token::<Secret, crate::ched::VTable>::default();
+        let expected = format!("<redacted {}>", any::type_name::<Secret>());
+        assert_eq!(Some(expected), debug_snapshot(&Secret(42)));
+    }
+
+    #[cfg(feature = "inventory")]
+    mod distributed {
+        use crate::vtable::{Registry, Specialise};
+
+        struct Custom;
+
+        struct VTable;
+
+        impl<T> Specialise<T> for VTable {
+            fn specialise() -> Self {
+                Self
+            }
+        }
+
+        crate::register_vtable!(Custom: VTable);
+
+        #[test]
+        fn collect_distributed() {
+            assert!(Registry::singleton().get::<Custom, VTable>().is_some());
+
+            let registry = Registry::new();
+            assert!(registry.get::<Custom, VTable>().is_none());
+            registry.collect_distributed();
+            assert!(registry.get::<Custom, VTable>().is_some());
+        }
+    }
+}
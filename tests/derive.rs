@@ -0,0 +1,50 @@
+//! The derived implementation refers to this crate by its external path, so it is exercised from
+//! an integration test rather than from within the crate.
+
+#![cfg(feature = "derive")]
+
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter};
+use std::hash::{DefaultHasher, Hasher};
+use vtable::every::Every;
+use vtable::vtable::{Registry, Specialise, Token};
+use vtable::{CloneFn, DebugFn, DisplayFn, OrdFn, PartialEqFn};
+
+#[derive(Specialise)]
+struct VTable {
+    clone: CloneFn,
+    fmt: DebugFn,
+    display: DisplayFn,
+    eq: PartialEqFn,
+    order: OrdFn,
+    partial_cmp: fn(&dyn Every, &dyn Every) -> Option<Ordering>,
+    hash: fn(&dyn Every, &mut dyn Hasher),
+}
+
+struct Rendered<'a>(&'a dyn Every, DebugFn);
+
+impl Debug for Rendered<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.1)(self.0, f)
+    }
+}
+
+#[test]
+fn derive_specialise() {
+    let vtable = Token::<u8, VTable>::in_registry(&Registry::new()).vtable_ref();
+    let cloned = (vtable.clone)(&42u8);
+    assert_eq!(Ok(&42u8), cloned.downcast_ref());
+    assert_eq!("42", format!("{:?}", Rendered(&42u8, vtable.fmt)));
+    assert_eq!("42", format!("{:?}", Rendered(&42u8, vtable.display)));
+    assert!((vtable.eq)(&42u8, &42u8));
+    assert!(!(vtable.eq)(&42u8, &42u16));
+    assert_eq!(Ordering::Less, (vtable.order)(&1u8, &2u8));
+    assert_eq!(Some(Ordering::Greater), (vtable.partial_cmp)(&2u8, &1u8));
+    assert!(<VTable as Specialise<u8>>::debug_fn().is_some());
+
+    let mut hasher = DefaultHasher::new();
+    (vtable.hash)(&42u8, &mut hasher);
+    let mut expected = DefaultHasher::new();
+    expected.write_u8(42);
+    assert_eq!(expected.finish(), hasher.finish());
+}